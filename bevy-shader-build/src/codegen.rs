@@ -0,0 +1,274 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{ShaderError, internals};
+
+/// The generated items that extra derives can be added to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratedItem {
+    /// The `ShaderEntry` enum
+    Entries,
+    /// Structs generated from WGSL struct declarations
+    Structs,
+    /// The `ShaderDataDetails` struct
+    Data,
+    /// The `BufferGroup` resource
+    Buffers,
+}
+
+/// Generates the rust bindings of a single WGSL shader
+/// ```ignore
+/// ShaderCodegen::new("assets/shaders/life.wgsl")
+///     .prefix("GameOfLife")
+///     .visibility("pub(crate)")
+///     .derive(GeneratedItem::Structs, "Debug")
+///     .compile()?;
+/// ```
+pub struct ShaderCodegen {
+    shader: PathBuf,
+    out_dir: Option<PathBuf>,
+    module_name: Option<String>,
+    prefix: Option<String>,
+    plugin_name: Option<String>,
+    entries_name: Option<String>,
+    data_name: Option<String>,
+    buffers_name: Option<String>,
+    visibility: Option<String>,
+    derives: Vec<(GeneratedItem, String)>,
+    format: bool,
+    asset_path: Option<String>,
+}
+
+impl ShaderCodegen {
+    pub fn new(shader: impl Into<PathBuf>) -> Self {
+        Self {
+            shader: shader.into(),
+            out_dir: None,
+            module_name: None,
+            prefix: None,
+            plugin_name: None,
+            entries_name: None,
+            data_name: None,
+            buffers_name: None,
+            visibility: None,
+            derives: vec![],
+            format: true,
+            asset_path: None,
+        }
+    }
+
+    /// Directory the module is written to, defaults to `$OUT_DIR`
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+
+        self
+    }
+
+    /// Name of the generated `<name>.rs` file, defaults to the shader file name
+    pub fn module_name(mut self, name: impl Into<String>) -> Self {
+        self.module_name = Some(name.into());
+
+        self
+    }
+
+    /// Prefix of every generated type name, defaults to the module name in PascalCase
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+
+        self
+    }
+
+    /// Overrides the `<prefix>ShaderPlugin` type alias name
+    pub fn plugin_name(mut self, name: impl Into<String>) -> Self {
+        self.plugin_name = Some(name.into());
+
+        self
+    }
+
+    /// Overrides the `<prefix>Entries` enum name
+    pub fn entries_name(mut self, name: impl Into<String>) -> Self {
+        self.entries_name = Some(name.into());
+
+        self
+    }
+
+    /// Overrides the `<prefix>Data` struct name
+    pub fn data_name(mut self, name: impl Into<String>) -> Self {
+        self.data_name = Some(name.into());
+
+        self
+    }
+
+    /// Overrides the `<prefix>Buffers` struct name
+    pub fn buffers_name(mut self, name: impl Into<String>) -> Self {
+        self.buffers_name = Some(name.into());
+
+        self
+    }
+
+    /// Visibility of every generated item and field, such as `pub(crate)`, defaults to `pub`
+    pub fn visibility(mut self, visibility: impl Into<String>) -> Self {
+        self.visibility = Some(visibility.into());
+
+        self
+    }
+
+    /// Adds a derive, such as `Debug` or `bevy::prelude::Reflect`, to a generated item
+    pub fn derive(mut self, item: GeneratedItem, derive: impl Into<String>) -> Self {
+        self.derives.push((item, derive.into()));
+
+        self
+    }
+
+    /// Whether the output is run through `prettyplease`, requires the `format` feature
+    pub fn format(mut self, format: bool) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    /// Asset path the generated plugin loads the shader from, such as `shaders/life.wgsl`
+    ///
+    /// Defaults to the shader path relative to its `assets` directory, shaders outside of one need
+    /// this or `ShaderBuilder::shader`
+    pub fn asset_path(mut self, path: impl Into<String>) -> Self {
+        self.asset_path = Some(path.into());
+
+        self
+    }
+
+    /// Writes the generated module, returning its path
+    pub fn compile(self) -> crate::Result<PathBuf> {
+        println!("cargo:rerun-if-changed={}", self.shader.display());
+
+        let name = self.name()?;
+        let codegen = self.codegen(&name)?;
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(ShaderError::NoOutDir)?,
+        };
+
+        let rust_file = internals::compile_shader(&codegen, &fs::read_to_string(&self.shader)?)?;
+
+        let rust_path = out_dir.join(format!("{name}.rs"));
+        fs::write(&rust_path, rust_file)?;
+
+        Ok(rust_path)
+    }
+
+    pub(crate) fn name(&self) -> crate::Result<String> {
+        match &self.module_name {
+            Some(name) => Ok(name.clone()),
+            None => module_name(&self.shader),
+        }
+    }
+
+    fn codegen(&self, name: &str) -> crate::Result<internals::Codegen> {
+        let mut codegen = internals::Codegen::new(self.prefix.as_deref().unwrap_or(name));
+        let names = [
+            (&self.plugin_name, &mut codegen.plugin),
+            (&self.entries_name, &mut codegen.entries),
+            (&self.data_name, &mut codegen.data),
+            (&self.buffers_name, &mut codegen.buffers),
+        ];
+        for (name, ident) in names {
+            if let Some(name) = name {
+                *ident = syn::parse_str(name)?;
+            }
+        }
+        if let Some(visibility) = &self.visibility {
+            codegen.vis = syn::parse_str(visibility)?;
+        }
+        codegen.derives = self
+            .derives
+            .iter()
+            .map(|(item, derive)| Ok((*item, syn::parse_str(derive)?)))
+            .collect::<crate::Result<_>>()?;
+        codegen.format = self.format;
+        codegen.asset_path = self.asset_path.clone().or_else(|| asset_path(&self.shader));
+
+        Ok(codegen)
+    }
+}
+
+/// The path after the last `assets` directory, which is where bevy loads assets from by default
+pub(crate) fn asset_path(shader: &Path) -> Option<String> {
+    let components: Vec<_> = shader.components().collect();
+    let assets = components
+        .iter()
+        .rposition(|c| *c == Component::Normal("assets".as_ref()))?;
+    let path: Vec<_> = components[assets + 1..]
+        .iter()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+
+    Some(path.join("/"))
+}
+
+pub(crate) fn module_name(shader: &Path) -> crate::Result<String> {
+    let name = shader.file_stem().ok_or(ShaderError::NoFileName)?;
+
+    Ok(name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_codegen_options() {
+        let out_dir = env::temp_dir().join("bevy-shader-build-codegen");
+        fs::create_dir_all(&out_dir).unwrap();
+
+        let res =
+            ShaderCodegen::new("../examples/hello_world_no_builder/assets/shaders/hello.wgsl")
+                .out_dir(&out_dir)
+                .module_name("generated")
+                .prefix("Greeting")
+                .buffers_name("GreetingResources")
+                .visibility("pub(crate)")
+                .derive(GeneratedItem::Structs, "Debug")
+                .derive(GeneratedItem::Entries, "Copy")
+                .format(false)
+                .compile()
+                .unwrap();
+        assert_eq!(res, out_dir.join("generated.rs"));
+
+        let res = fs::read_to_string(res).unwrap();
+        assert!(res.contains(
+            "pub (crate) type GreetingShaderPlugin = ShaderPlugin < GreetingData , GreetingEntries , GreetingResources"
+        ));
+        assert!(res.contains("# [derive (ShaderType , Clone , Debug)] pub (crate) struct Foo"));
+        assert!(res.contains("Hash , Clone , Copy)] pub (crate) enum GreetingEntries"));
+        assert!(res.contains("pub (crate) a : Vec < u32 >"));
+        assert!(res.contains("# [shader (\"shaders/hello.wgsl\")]"));
+    }
+
+    #[test_log::test]
+    fn test_asset_path() {
+        assert_eq!(
+            asset_path(Path::new("assets/shaders/hello.wgsl")).as_deref(),
+            Some("shaders/hello.wgsl")
+        );
+        assert_eq!(
+            asset_path(Path::new("../game/assets/life.wgsl")).as_deref(),
+            Some("life.wgsl")
+        );
+        assert_eq!(asset_path(Path::new("src/hello.wgsl")), None);
+    }
+
+    #[test_log::test]
+    fn test_invalid_options() {
+        let res =
+            ShaderCodegen::new("../examples/hello_world_no_builder/assets/shaders/hello.wgsl")
+                .out_dir(env::temp_dir())
+                .visibility("public")
+                .compile();
+
+        assert!(matches!(res, Err(ShaderError::CodegenError(_))));
+    }
+}
@@ -1,129 +1,522 @@
-#![allow(unused_variables, dead_code)]
-use naga::{
-    Arena, EntryPoint, GlobalVariable, Handle, ImageClass, ImageDimension, Scalar,
-    StructMember, Type, TypeInner, UniqueArena, front::wgsl,
-};
-use thiserror::Error;
-use tracing::{debug, info};
-use wgpu_types::ShaderStages;
+mod buffers;
+mod data;
+mod entries;
+mod types;
 
-pub(crate) fn compile_shader<T1: AsRef<str>, T2: AsRef<str>, T3: AsRef<str>>(
-    name: T1,
-    shader: T2,
-    shader_path: T3,
-) -> crate::Result<String> {
-    let shader = wgsl::parse_str(shader.as_ref())?;
+use naga::front::wgsl;
+use proc_macro2::{Ident, Span};
+use quote::{ToTokens, quote};
+use syn::{Path, Visibility};
+use thiserror::Error;
+use tracing::debug;
 
-    debug!(?shader);
+use crate::GeneratedItem;
 
-    todo!()
+/// Resolved code generation options, see [`crate::ShaderCodegen`]
+pub(crate) struct Codegen {
+    pub(crate) plugin: Ident,
+    pub(crate) entries: Ident,
+    pub(crate) data: Ident,
+    pub(crate) buffers: Ident,
+    pub(crate) vis: Visibility,
+    pub(crate) derives: Vec<(GeneratedItem, Path)>,
+    pub(crate) format: bool,
+    /// Asset path the data struct loads the shader from
+    pub(crate) asset_path: Option<String>,
 }
 
-#[derive(Error, Debug)]
-pub enum EntryError {
-    #[error("Shader has multiple stages")]
-    MultipleStages, // For now if a shader has multi-stages in one file this will fail
-    #[error("Shader has no stages")]
-    NoStages,
-}
+impl Codegen {
+    pub(crate) fn new(name: &str) -> Self {
+        let prefix = entries::pascal_case(name);
+
+        Self {
+            plugin: Self::ident(&prefix, "ShaderPlugin"),
+            entries: Self::ident(&prefix, "Entries"),
+            data: Self::ident(&prefix, "Data"),
+            buffers: Self::ident(&prefix, "Buffers"),
+            vis: syn::parse_quote! { pub },
+            derives: vec![],
+            format: true,
+            asset_path: None,
+        }
+    }
 
-// TODO: better error return, i'm just being lazy
-// TODO: return a bitflag result to allow for multiple shader stages to be acknowledged
-fn handle_entries(entries: &[EntryPoint]) -> crate::Result<ShaderStages> {
-    let _ = entries
-        .iter()
-        .map(|entry| (&entry.name, entry.stage));
+    pub(crate) fn ident(prefix: &str, suffix: &str) -> Ident {
+        Ident::new(&format!("{prefix}{suffix}"), Span::call_site())
+    }
 
-    todo!()
+    pub(crate) fn derives(&self, item: GeneratedItem) -> impl Iterator<Item = &Path> {
+        self.derives
+            .iter()
+            .filter(move |(i, _)| *i == item)
+            .map(|(_, path)| path)
+    }
 }
 
-fn basic_type(scalar: &Scalar) -> u32 {
-    info!(?scalar);
+pub(crate) fn compile_shader(codegen: &Codegen, shader: &str) -> crate::Result<String> {
+    let shader = wgsl::parse_str(shader)?;
 
-    scalar.width as u32
-}
+    debug!(?shader);
 
-fn vector_type(scalar: &Scalar) -> u32 {
-    info!(?scalar);
+    let entries = entries::handle_entries(&shader.entry_points)?;
+    let entries_enum = entries.enum_item(codegen);
+    let structs = types::binding_structs(&shader)
+        .into_iter()
+        .map(|ty| types::struct_item(ty, &shader.types, codegen))
+        .collect::<crate::Result<Vec<_>>>()?;
+    let bindings = buffers::handle_buffers(&shader)?;
+    let data_struct = data::data_item(codegen, &entries, &bindings, &shader)?;
+    let buffers_struct = buffers::buffers_item(codegen, &bindings);
+    let plugin = data::plugin_item(codegen, bindings.len(), entries.names().len());
 
-    scalar.width as u32
-}
+    let file: syn::File = syn::parse2(quote! {
+        use bevy_shader_helper::internals::prelude::*;
 
-fn array_type(base: &Handle<Type>, types: &UniqueArena<Type>) -> u32 {
-    let base = types
-        .get_handle(*base)
-        .expect("Failed to get shader array base data type");
-    info!(?base);
+        #plugin
 
-    0
-}
+        #entries_enum
 
-fn struct_type(members: &[StructMember], _types: &UniqueArena<Type>, span: &u32) -> u32 {
-    let members: Vec<_> = members
-        .iter()
-        .map(|mem| _types.get_handle(mem.ty).expect("Failed to get shader struct member types"))
-        .collect();
-    info!(?members, ?span);
+        #(#structs)*
 
-    *span
-}
+        #data_struct
 
-fn texture_type(dim: &ImageDimension, class: &ImageClass) -> u32 {
-    info!(?dim, ?class);
+        #buffers_struct
+    })?;
 
-    0
+    Ok(render(&file, codegen.format))
 }
 
-// TODO: fail if no buffers
-// TODO: should probably use naga size hints rather then rust size_of::<T>()
-// TODO: should handle pre-sized arrays, if they exist
-fn handle_buffers(
-    variables: &Arena<GlobalVariable>,
-    types: &UniqueArena<Type>,
-) -> crate::Result<()> {
-    for v in variables.iter() {
-        let ty = v.1.ty;
-        let ty = types
-            .get_handle(ty)
-            .expect("Failed to get shader data type");
-        let ty = &ty.inner;
-        match ty {
-            TypeInner::Scalar(scalar) => basic_type(scalar),
-            TypeInner::Atomic(scalar) => basic_type(scalar),
-            TypeInner::Vector { size: _, scalar } => vector_type(scalar),
-            TypeInner::Array {
-                base,
-                size: _,
-                stride: _,
-            } => array_type(base, types),
-            TypeInner::Struct { members, span } => struct_type(members, types, span),
-            TypeInner::Image {
-                dim,
-                arrayed: _,
-                class,
-            } => texture_type(dim, class),
-            _ => todo!("Unhandled buffer type"),
-        };
+#[cfg_attr(not(feature = "format"), allow(unused_variables))]
+fn render(file: &syn::File, format: bool) -> String {
+    #[cfg(feature = "format")]
+    if format {
+        return prettyplease::unparse(file);
     }
 
-    Ok(())
+    file.to_token_stream().to_string()
+}
+
+#[derive(Error, Debug)]
+pub enum EntryError {
+    #[error("Shader has no compute entry points")]
+    NoStages,
+    #[error("Shader has multiple entry points named {0}")]
+    DuplicateEntry(String),
+}
+
+#[derive(Error, Debug)]
+pub enum TypeError {
+    #[error("Unsupported shader type {0}")]
+    Unsupported(String),
+    #[error("Shader structs must be named")]
+    AnonymousStruct,
+    #[error("Shader struct members must be named")]
+    AnonymousMember,
+}
+
+#[derive(Error, Debug)]
+pub enum BufferError {
+    #[error("Shader has no bindings")]
+    NoBindings,
+    #[error("Shader bindings must be named")]
+    AnonymousBinding,
+    #[error("Binding {0} uses an unsupported address space {1}")]
+    UnsupportedBinding(String, String),
+    #[error("Binding {0} uses an unsupported storage texture dimension {1}")]
+    UnsupportedTexture(String, String),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "format")]
+    /// Asserts every item of `expected` is generated, comparing parsed items instead of text
+    ///
+    /// Needs the formatted output, which keeps the trailing commas `parse_quote!` items have
+    fn assert_items(res: &str, expected: syn::File) {
+        let generated: syn::File = syn::parse_str(res).unwrap();
+        let items: Vec<_> = generated
+            .items
+            .iter()
+            .map(|item| item.to_token_stream().to_string())
+            .collect();
+        for item in expected.items {
+            let item = item.to_token_stream().to_string();
+            assert!(items.contains(&item), "{item}\nis not generated in\n{res}");
+        }
+    }
+
     #[test_log::test]
     fn test_invalid_shader() {
-        let res = compile_shader("test", "foo", "");
+        let res = compile_shader(&Codegen::new("test"), "foo");
 
         assert!(res.is_err())
     }
 
     #[test_log::test]
     fn test_entry_points() {
-        let res = compile_shader("test", r#"@compute @workgroup_size(1) fn main() {}"#, "");
+        let shader = r#"
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("test"), shader);
 
         assert!(res.is_ok())
     }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_entries_enum() {
+        let shader = r#"
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @compute @workgroup_size(1) fn main() {}
+            @compute @workgroup_size(1) fn update_cells() {}
+            @vertex fn vertex() -> @builtin(position) vec4<f32> { return vec4<f32>(); }
+        "#;
+        let res = compile_shader(&Codegen::new("hello_world"), shader).unwrap();
+
+        let expected: syn::File = syn::parse_quote! {
+            #[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+            pub enum HelloWorldEntries {
+                Main,
+                UpdateCells,
+            }
+        };
+
+        assert_items(&res, expected);
+        assert!(!res.contains("Vertex"));
+    }
+
+    #[test_log::test]
+    fn test_entry_attributes() {
+        let shader = wgsl::parse_str(
+            r#"
+            @compute @workgroup_size(1) fn main() {}
+            @compute @workgroup_size(1) fn update() {}
+        "#,
+        )
+        .unwrap();
+        let entries = entries::handle_entries(&shader.entry_points).unwrap();
+
+        assert_eq!(
+            entries.attributes().to_string(),
+            quote! { #[entry("main")] #[entry("update")] }.to_string()
+        );
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_binding_structs() {
+        let shader = r#"
+            struct Inner {
+                value: vec3<f32>,
+            }
+            struct Foo {
+                bar: u32,
+                inner: array<Inner, 4>,
+                transform: mat4x4<f32>,
+                cells: array<vec2<i32>>,
+            }
+            struct Unused {
+                a: u32,
+            }
+            @group(0) @binding(0) var<storage, read> a: Foo;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("test"), shader).unwrap();
+        let expected: syn::File = syn::parse_quote! {
+            #[derive(ShaderType, Clone)]
+            pub struct Inner {
+                pub value: Vec3,
+            }
+            #[derive(ShaderType, Clone)]
+            pub struct Foo {
+                pub bar: u32,
+                pub inner: [Inner; 4],
+                pub transform: Mat4,
+                #[size(runtime)]
+                pub cells: Vec<IVec2>,
+            }
+        };
+
+        assert_items(&res, expected);
+        assert!(!res.contains("Unused"));
+    }
+
+    #[test_log::test]
+    fn test_unsupported_struct_member() {
+        let shader = r#"
+            struct Foo {
+                bar: mat2x3<f32>,
+            }
+            @group(0) @binding(0) var<storage, read> a: Foo;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("test"), shader);
+
+        assert!(matches!(
+            res,
+            Err(crate::ShaderError::TypeError(TypeError::Unsupported(_)))
+        ))
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_buffer_group() {
+        let shader = r#"
+            @group(0) @binding(1) var<storage, read>       b: vec3<f32>;
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @group(0) @binding(2) var                      c: texture_storage_2d<r32float, read_write>;
+            @group(0) @binding(3) var                      d: texture_storage_2d<rgba8unorm, write>;
+            @group(0) @binding(4) var                      e: texture_storage_2d<rgba8unorm, read>;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("hello"), shader).unwrap();
+        let expected: syn::File = syn::parse_quote! {
+            #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+            #[data(HelloData)]
+            pub struct HelloBuffers {
+                #[writeable]
+                pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+                pub b: ReadBuffer<ShaderStorageBuffer>,
+                #[writeable]
+                #[texture]
+                pub c: ReadWriteBuffer<Image>,
+                #[writeable]
+                #[texture]
+                pub d: WriteBuffer<Image>,
+                #[texture]
+                pub e: ReadBuffer<Image>,
+            }
+        };
+
+        assert_items(&res, expected);
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_example_shader() {
+        let shader =
+            include_str!("../../examples/hello_world_no_builder/assets/shaders/hello.wgsl");
+        let mut codegen = Codegen::new("hello");
+        codegen.asset_path = Some("shaders/hello.wgsl".into());
+        let res = compile_shader(&codegen, shader).unwrap();
+        let expected: syn::File = syn::parse_str(include_str!(
+            "../../examples/hello_world_no_builder/src/shader.rs"
+        ))
+        .unwrap();
+
+        assert_eq!(res, render(&expected, true));
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_data_details() {
+        let shader = r#"
+            @group(0) @binding(0) var<storage, read_write> a: array<vec4<f32>>;
+            @group(0) @binding(1) var<storage, read>       b: array<i32, 4>;
+            @group(0) @binding(2) var                      c: texture_storage_2d_array<rgba32float, write>;
+            @group(0) @binding(3) var                      d: texture_storage_3d<r32uint, read>;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("hello"), shader).unwrap();
+        let expected: syn::File = syn::parse_quote! {
+            #[derive(ShaderDataDetails, Clone)]
+            #[entry("main")]
+            pub struct HelloData {
+                pub a: Vec<Vec4>,
+                #[read_only]
+                pub b: [i32; 4],
+                #[texture(WriteOnly, Rgba32Float, D2Array)]
+                pub c: ImageBuilder<Rgba32Float, D2Array>,
+                #[texture(ReadOnly, R32Uint, D3)]
+                pub d: ImageBuilder<R32Uint, D3>,
+            }
+            pub type HelloShaderPlugin = ShaderPlugin<HelloData, HelloEntries, HelloBuffers, 4, 1>;
+        };
+
+        assert_items(&res, expected);
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_uniform_binding() {
+        let shader = r#"
+            struct Params {
+                size: vec2<u32>,
+                scale: f32,
+            }
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @group(0) @binding(1) var<uniform>             b: Params;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("hello"), shader).unwrap();
+        let data: syn::File = syn::parse_quote! {
+            #[derive(ShaderDataDetails, Clone)]
+            #[entry("main")]
+            pub struct HelloData {
+                pub a: Vec<u32>,
+                #[uniform]
+                pub b: Params,
+            }
+        };
+        let buffers: syn::File = syn::parse_quote! {
+            #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+            #[data(HelloData)]
+            pub struct HelloBuffers {
+                #[writeable]
+                pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+                #[uniform]
+                pub b: UniformBuffer<ShaderStorageBuffer>,
+            }
+        };
+
+        assert_items(&res, data);
+        assert_items(&res, buffers);
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_binding_indices() {
+        let shader = r#"
+            @group(0) @binding(3) var<storage, read>       b: u32;
+            @group(0) @binding(1) var<storage, read_write> a: array<u32>;
+            @group(0) @binding(4) var<uniform>             c: f32;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("hello"), shader).unwrap();
+        let data: syn::File = syn::parse_quote! {
+            #[derive(ShaderDataDetails, Clone)]
+            #[entry("main")]
+            pub struct HelloData {
+                #[binding(1)]
+                pub a: Vec<u32>,
+                #[binding(3)]
+                #[read_only]
+                pub b: u32,
+                #[uniform]
+                pub c: f32,
+            }
+        };
+        let buffers: syn::File = syn::parse_quote! {
+            #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+            #[data(HelloData)]
+            pub struct HelloBuffers {
+                #[binding(1)]
+                #[writeable]
+                pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+                #[binding(3)]
+                pub b: ReadBuffer<ShaderStorageBuffer>,
+                #[uniform]
+                pub c: UniformBuffer<ShaderStorageBuffer>,
+            }
+        };
+
+        assert_items(&res, data);
+        assert_items(&res, buffers);
+    }
+
+    #[cfg(feature = "format")]
+    #[test_log::test]
+    fn test_binding_groups() {
+        let shader = r#"
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @group(1) @binding(0) var<uniform>             b: f32;
+            @group(1) @binding(1) var<storage, read>       c: u32;
+            @group(2) @binding(2) var<storage, read>       d: u32;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("hello"), shader).unwrap();
+        let data: syn::File = syn::parse_quote! {
+            #[derive(ShaderDataDetails, Clone)]
+            #[entry("main")]
+            pub struct HelloData {
+                pub a: Vec<u32>,
+                #[group(1)]
+                #[uniform]
+                pub b: f32,
+                #[group(1)]
+                #[read_only]
+                pub c: u32,
+                #[group(2)]
+                #[binding(2)]
+                #[read_only]
+                pub d: u32,
+            }
+        };
+        let buffers: syn::File = syn::parse_quote! {
+            #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+            #[data(HelloData)]
+            pub struct HelloBuffers {
+                #[writeable]
+                pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+                #[group(1)]
+                #[uniform]
+                pub b: UniformBuffer<ShaderStorageBuffer>,
+                #[group(1)]
+                pub c: ReadBuffer<ShaderStorageBuffer>,
+                #[group(2)]
+                #[binding(2)]
+                pub d: ReadBuffer<ShaderStorageBuffer>,
+            }
+        };
+
+        assert_items(&res, data);
+        assert_items(&res, buffers);
+    }
+
+    #[test_log::test]
+    fn test_buffer_errors() {
+        let res = compile_shader(
+            &Codegen::new("test"),
+            "@compute @workgroup_size(1) fn main() {}",
+        );
+        assert!(matches!(
+            res,
+            Err(crate::ShaderError::BufferError(BufferError::NoBindings))
+        ));
+
+        let shader = r#"
+            @group(0) @binding(0) var a: sampler;
+            @compute @workgroup_size(1) fn main() {}
+        "#;
+        let res = compile_shader(&Codegen::new("test"), shader);
+        assert!(matches!(
+            res,
+            Err(crate::ShaderError::BufferError(
+                BufferError::UnsupportedBinding(..)
+            ))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_no_compute_entries() {
+        let res = compile_shader(
+            &Codegen::new("test"),
+            r#"@vertex fn main() -> @builtin(position) vec4<f32> { return vec4<f32>(); }"#,
+        );
+
+        assert!(matches!(
+            res,
+            Err(crate::ShaderError::EntryError(EntryError::NoStages))
+        ))
+    }
+
+    #[test_log::test]
+    fn test_duplicate_entries() {
+        let shader = r#"
+            @group(0) @binding(0) var<storage, read_write> a: array<u32>;
+            @compute @workgroup_size(1) fn update_cells() {}
+            @compute @workgroup_size(1) fn updateCells() {}
+        "#;
+        let res = compile_shader(&Codegen::new("test"), shader);
+
+        assert!(matches!(
+            res,
+            Err(crate::ShaderError::EntryError(EntryError::DuplicateEntry(
+                _
+            )))
+        ))
+    }
 }
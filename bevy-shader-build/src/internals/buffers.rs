@@ -0,0 +1,169 @@
+use naga::{
+    AddressSpace, Handle, ImageClass, ImageDimension, Module, StorageAccess, StorageFormat, Type,
+    TypeInner,
+};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use syn::Visibility;
+use tracing::debug;
+
+use super::{BufferError, Codegen, types::field_ident};
+use crate::GeneratedItem;
+
+pub(crate) struct Binding {
+    pub(crate) ident: Ident,
+    pub(crate) group: u32,
+    pub(crate) binding: u32,
+    pub(crate) ty: Handle<Type>,
+    pub(crate) kind: BindingKind,
+    /// Whether the index does not follow the previous binding of its group, and must be written out
+    pub(crate) explicit: bool,
+}
+
+pub(crate) enum BindingKind {
+    Storage {
+        access: StorageAccess,
+    },
+    StorageTexture {
+        access: StorageAccess,
+        format: StorageFormat,
+        dim: ImageDimension,
+        arrayed: bool,
+    },
+    Uniform,
+}
+
+impl Binding {
+    /// The GPU (shader) side access of the binding
+    fn access(&self) -> StorageAccess {
+        match self.kind {
+            BindingKind::Storage { access } | BindingKind::StorageTexture { access, .. } => access,
+            BindingKind::Uniform => StorageAccess::LOAD,
+        }
+    }
+
+    pub(crate) fn index_attribute(&self) -> TokenStream {
+        let group = Literal::u32_unsuffixed(self.group);
+        let index = Literal::u32_unsuffixed(self.binding);
+        let group = (self.group != 0).then(|| quote! { #[group(#group)] });
+        let binding = self.explicit.then(|| quote! { #[binding(#index)] });
+
+        quote! { #group #binding }
+    }
+
+    fn is_texture(&self) -> bool {
+        matches!(self.kind, BindingKind::StorageTexture { .. })
+    }
+
+    pub(crate) fn buffer_field(&self, vis: &Visibility) -> TokenStream {
+        let ident = &self.ident;
+        let index = self.index_attribute();
+        if matches!(self.kind, BindingKind::Uniform) {
+            return quote! {
+                #index
+                #[uniform]
+                #vis #ident: UniformBuffer<ShaderStorageBuffer>
+            };
+        }
+
+        let access = self.access();
+        let wrapper = if access.contains(StorageAccess::LOAD | StorageAccess::STORE) {
+            quote! { ReadWriteBuffer }
+        } else if access.contains(StorageAccess::STORE) {
+            quote! { WriteBuffer }
+        } else {
+            quote! { ReadBuffer }
+        };
+        let writeable = access
+            .contains(StorageAccess::STORE)
+            .then(|| quote! { #[writeable] });
+        let (texture, asset) = if self.is_texture() {
+            (Some(quote! { #[texture] }), quote! { Image })
+        } else {
+            (None, quote! { ShaderStorageBuffer })
+        };
+
+        quote! {
+            #index
+            #writeable
+            #texture
+            #vis #ident: #wrapper<#asset>
+        }
+    }
+}
+
+pub(crate) fn handle_buffers(module: &Module) -> crate::Result<Vec<Binding>> {
+    let mut bindings = vec![];
+    for (_, variable) in module.global_variables.iter() {
+        let Some(binding) = &variable.binding else {
+            continue;
+        };
+        let name = variable
+            .name
+            .as_ref()
+            .ok_or(BufferError::AnonymousBinding)?;
+        let kind = match (variable.space, &module.types[variable.ty].inner) {
+            (AddressSpace::Storage { access }, _) => BindingKind::Storage { access },
+            (AddressSpace::Uniform, _) => BindingKind::Uniform,
+            (
+                AddressSpace::Handle,
+                TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class: ImageClass::Storage { format, access },
+                },
+            ) => BindingKind::StorageTexture {
+                access: *access,
+                format: *format,
+                dim: *dim,
+                arrayed: *arrayed,
+            },
+            (space, _) => Err(BufferError::UnsupportedBinding(
+                name.clone(),
+                format!("{space:?}"),
+            ))?,
+        };
+
+        bindings.push(Binding {
+            ident: field_ident(name),
+            group: binding.group,
+            binding: binding.binding,
+            ty: variable.ty,
+            kind,
+            explicit: false,
+        });
+    }
+
+    if bindings.is_empty() {
+        Err(BufferError::NoBindings)?
+    }
+
+    bindings.sort_by_key(|b| (b.group, b.binding));
+    let mut next = (0, 0);
+    for binding in &mut bindings {
+        if binding.group != next.0 {
+            next = (binding.group, 0);
+        }
+        binding.explicit = binding.binding != next.1;
+        next.1 = binding.binding + 1;
+    }
+    debug!(bindings = bindings.len());
+
+    Ok(bindings)
+}
+
+pub(crate) fn buffers_item(codegen: &Codegen, bindings: &[Binding]) -> TokenStream {
+    let Codegen {
+        buffers, data, vis, ..
+    } = codegen;
+    let fields = bindings.iter().map(|binding| binding.buffer_field(vis));
+    let derives = codegen.derives(GeneratedItem::Buffers);
+
+    quote! {
+        #[derive(Resource, ExtractResource, Clone, BufferGroup #(, #derives)*)]
+        #[data(#data)]
+        #vis struct #buffers {
+            #(#fields),*
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use naga::{ImageDimension, Module, StorageAccess};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::Visibility;
+
+use super::{
+    BufferError, Codegen,
+    buffers::{Binding, BindingKind},
+    entries::Entries,
+    types::rust_type,
+};
+use crate::GeneratedItem;
+
+impl Binding {
+    pub(crate) fn data_field(
+        &self,
+        module: &Module,
+        vis: &Visibility,
+    ) -> crate::Result<TokenStream> {
+        let ident = &self.ident;
+        let index = self.index_attribute();
+        let field = match &self.kind {
+            BindingKind::Storage { access } => {
+                let ty = rust_type(self.ty, &module.types)?;
+                let read_only =
+                    (!access.contains(StorageAccess::STORE)).then(|| quote! { #[read_only] });
+
+                quote! {
+                    #read_only
+                    #vis #ident: #ty
+                }
+            }
+            BindingKind::Uniform => {
+                let ty = rust_type(self.ty, &module.types)?;
+
+                quote! {
+                    #[uniform]
+                    #vis #ident: #ty
+                }
+            }
+            BindingKind::StorageTexture {
+                access,
+                format,
+                dim,
+                arrayed,
+            } => {
+                let access = if access.contains(StorageAccess::LOAD | StorageAccess::STORE) {
+                    quote! { ReadWrite }
+                } else if access.contains(StorageAccess::STORE) {
+                    quote! { WriteOnly }
+                } else {
+                    quote! { ReadOnly }
+                };
+                let format = Ident::new(&format!("{format:?}"), Span::call_site());
+                let view = match (dim, arrayed) {
+                    (ImageDimension::D1, false) => quote! { D1 },
+                    (ImageDimension::D2, false) => quote! { D2 },
+                    (ImageDimension::D2, true) => quote! { D2Array },
+                    (ImageDimension::D3, false) => quote! { D3 },
+                    _ => Err(BufferError::UnsupportedTexture(
+                        ident.to_string(),
+                        format!("{dim:?}"),
+                    ))?,
+                };
+
+                quote! {
+                    #[texture(#access, #format, #view)]
+                    #vis #ident: ImageBuilder<#format, #view>
+                }
+            }
+        };
+
+        Ok(quote! {
+            #index
+            #field
+        })
+    }
+}
+
+pub(crate) fn data_item(
+    codegen: &Codegen,
+    entries: &Entries,
+    bindings: &[Binding],
+    module: &Module,
+) -> crate::Result<TokenStream> {
+    let Codegen {
+        data,
+        vis,
+        asset_path,
+        ..
+    } = codegen;
+    let shader = asset_path.as_ref().map(|path| quote! { #[shader(#path)] });
+    let attributes = entries.attributes();
+    let fields = bindings
+        .iter()
+        .map(|binding| binding.data_field(module, vis))
+        .collect::<crate::Result<Vec<_>>>()?;
+    let derives = codegen.derives(GeneratedItem::Data);
+
+    Ok(quote! {
+        #[derive(ShaderDataDetails, Clone #(, #derives)*)]
+        #shader
+        #attributes
+        #vis struct #data {
+            #(#fields),*
+        }
+    })
+}
+
+pub(crate) fn plugin_item(
+    codegen: &Codegen,
+    binding_count: usize,
+    entry_count: usize,
+) -> TokenStream {
+    let Codegen {
+        plugin,
+        entries,
+        data,
+        buffers,
+        vis,
+        ..
+    } = codegen;
+    let binding_count = Literal::usize_unsuffixed(binding_count);
+    let entry_count = Literal::usize_unsuffixed(entry_count);
+
+    quote! {
+        #vis type #plugin = ShaderPlugin<#data, #entries, #buffers, #binding_count, #entry_count>;
+    }
+}
@@ -0,0 +1,82 @@
+use naga::{EntryPoint, ShaderStage};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use tracing::debug;
+
+use super::{Codegen, EntryError};
+use crate::GeneratedItem;
+
+pub(crate) struct Entries {
+    names: Vec<String>,
+}
+
+impl Entries {
+    pub(crate) fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn variants(&self) -> impl Iterator<Item = Ident> + '_ {
+        self.names
+            .iter()
+            .map(|name| Ident::new(&pascal_case(name), Span::call_site()))
+    }
+
+    pub(crate) fn enum_item(&self, codegen: &Codegen) -> TokenStream {
+        let Codegen { entries, vis, .. } = codegen;
+        let variants = self.variants();
+        let derives = codegen.derives(GeneratedItem::Entries);
+
+        quote! {
+            #[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone #(, #derives)*)]
+            #vis enum #entries {
+                #(#variants),*
+            }
+        }
+    }
+
+    // The order must match the enum variants, as ShaderEntry keys are the variant index
+    pub(crate) fn attributes(&self) -> TokenStream {
+        let names = &self.names;
+
+        quote! {
+            #(#[entry(#names)])*
+        }
+    }
+}
+
+pub(crate) fn handle_entries(entries: &[EntryPoint]) -> crate::Result<Entries> {
+    let mut variants: Vec<String> = vec![];
+    let mut names = vec![];
+    for entry in entries {
+        if entry.stage != ShaderStage::Compute {
+            debug!(entry.name, ?entry.stage, "Skipping non compute entry");
+            continue;
+        }
+
+        let variant = pascal_case(&entry.name);
+        if variants.contains(&variant) {
+            Err(EntryError::DuplicateEntry(entry.name.clone()))?
+        }
+        variants.push(variant);
+        names.push(entry.name.clone());
+    }
+
+    if names.is_empty() {
+        Err(EntryError::NoStages)?
+    }
+
+    Ok(Entries { names })
+}
+
+pub(crate) fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,181 @@
+use naga::{
+    ArraySize, Handle, Module, Scalar, ScalarKind, StructMember, Type, TypeInner, UniqueArena,
+    VectorSize,
+};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::Visibility;
+use tracing::debug;
+
+use super::{Codegen, TypeError};
+use crate::GeneratedItem;
+
+/// Maps a host-shareable WGSL type onto the rust type used to fill its buffer.
+pub(crate) fn rust_type(ty: Handle<Type>, types: &UniqueArena<Type>) -> crate::Result<TokenStream> {
+    let ty = &types[ty];
+    let rust = match &ty.inner {
+        TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => scalar_type(scalar)?,
+        TypeInner::Vector { size, scalar } => vector_type(size, scalar)?,
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => matrix_type(columns, rows, scalar)?,
+        TypeInner::Array {
+            base,
+            size: ArraySize::Constant(size),
+            ..
+        } => {
+            let base = rust_type(*base, types)?;
+            let size = Literal::u32_unsuffixed(size.get());
+            quote! { [#base; #size] }
+        }
+        TypeInner::Array {
+            base,
+            size: ArraySize::Dynamic,
+            ..
+        } => {
+            let base = rust_type(*base, types)?;
+            quote! { Vec<#base> }
+        }
+        TypeInner::Struct { .. } => {
+            let ident = struct_ident(ty)?;
+            quote! { #ident }
+        }
+        inner => Err(TypeError::Unsupported(format!("{inner:?}")))?,
+    };
+
+    Ok(rust)
+}
+
+fn scalar_type(scalar: &Scalar) -> crate::Result<TokenStream> {
+    let rust = match (scalar.kind, scalar.width) {
+        (ScalarKind::Sint, 4) => quote! { i32 },
+        (ScalarKind::Uint, 4) => quote! { u32 },
+        (ScalarKind::Float, 4) => quote! { f32 },
+        _ => Err(TypeError::Unsupported(format!("{scalar:?}")))?,
+    };
+
+    Ok(rust)
+}
+
+fn vector_type(size: &VectorSize, scalar: &Scalar) -> crate::Result<TokenStream> {
+    let prefix = match (scalar.kind, scalar.width) {
+        (ScalarKind::Sint, 4) => "I",
+        (ScalarKind::Uint, 4) => "U",
+        (ScalarKind::Float, 4) => "",
+        _ => Err(TypeError::Unsupported(format!(
+            "vec{}<{scalar:?}>",
+            *size as u8
+        )))?,
+    };
+    let ident = Ident::new(&format!("{prefix}Vec{}", *size as u8), Span::call_site());
+
+    Ok(quote! { #ident })
+}
+
+fn matrix_type(
+    columns: &VectorSize,
+    rows: &VectorSize,
+    scalar: &Scalar,
+) -> crate::Result<TokenStream> {
+    let rust = match (columns, rows, scalar.kind, scalar.width) {
+        (VectorSize::Bi, VectorSize::Bi, ScalarKind::Float, 4) => quote! { Mat2 },
+        (VectorSize::Tri, VectorSize::Tri, ScalarKind::Float, 4) => quote! { Mat3 },
+        (VectorSize::Quad, VectorSize::Quad, ScalarKind::Float, 4) => quote! { Mat4 },
+        _ => Err(TypeError::Unsupported(format!(
+            "mat{}x{}<{scalar:?}>",
+            *columns as u8, *rows as u8
+        )))?,
+    };
+
+    Ok(rust)
+}
+
+fn struct_ident(ty: &Type) -> crate::Result<Ident> {
+    let name = ty.name.as_ref().ok_or(TypeError::AnonymousStruct)?;
+
+    Ok(Ident::new(name, Span::call_site()))
+}
+
+pub(crate) fn field_ident(name: &str) -> Ident {
+    syn::parse_str(name).unwrap_or_else(|_| Ident::new_raw(name, Span::call_site()))
+}
+
+/// Every struct used by a binding, ordered so nested structs come before their users.
+pub(crate) fn binding_structs(module: &Module) -> Vec<Handle<Type>> {
+    let mut structs = vec![];
+    for (_, variable) in module.global_variables.iter() {
+        if variable.binding.is_some() {
+            collect_structs(variable.ty, &module.types, &mut structs);
+        }
+    }
+
+    structs
+}
+
+fn collect_structs(ty: Handle<Type>, types: &UniqueArena<Type>, structs: &mut Vec<Handle<Type>>) {
+    match &types[ty].inner {
+        TypeInner::Array { base, .. } => collect_structs(*base, types, structs),
+        TypeInner::Struct { members, .. } => {
+            if structs.contains(&ty) {
+                return;
+            }
+            for member in members {
+                collect_structs(member.ty, types, structs);
+            }
+            structs.push(ty);
+        }
+        _ => {}
+    }
+}
+
+// TODO: handle @size and @align member attributes
+pub(crate) fn struct_item(
+    ty: Handle<Type>,
+    types: &UniqueArena<Type>,
+    codegen: &Codegen,
+) -> crate::Result<TokenStream> {
+    let TypeInner::Struct { members, span } = &types[ty].inner else {
+        unreachable!("Only structs are collected")
+    };
+    debug!(?members, ?span);
+
+    let ident = struct_ident(&types[ty])?;
+    let fields = members
+        .iter()
+        .map(|member| struct_field(member, types, &codegen.vis))
+        .collect::<crate::Result<Vec<_>>>()?;
+    let vis = &codegen.vis;
+    let derives = codegen.derives(GeneratedItem::Structs);
+
+    Ok(quote! {
+        #[derive(ShaderType, Clone #(, #derives)*)]
+        #vis struct #ident {
+            #(#fields),*
+        }
+    })
+}
+
+fn struct_field(
+    member: &StructMember,
+    types: &UniqueArena<Type>,
+    vis: &Visibility,
+) -> crate::Result<TokenStream> {
+    let name = member.name.as_ref().ok_or(TypeError::AnonymousMember)?;
+    let ident = field_ident(name);
+    let ty = rust_type(member.ty, types)?;
+    let runtime = matches!(
+        types[member.ty].inner,
+        TypeInner::Array {
+            size: ArraySize::Dynamic,
+            ..
+        }
+    )
+    .then(|| quote! { #[size(runtime)] });
+
+    Ok(quote! {
+        #runtime
+        #vis #ident: #ty
+    })
+}
@@ -1,12 +1,14 @@
+mod codegen;
 mod internals;
 
 use std::{
-    fs::{self, File},
-    io::Write,
-    path::PathBuf,
+    fs,
+    path::{Path, PathBuf},
 };
 
-use internals::EntryError;
+pub use codegen::{GeneratedItem, ShaderCodegen};
+
+use internals::{BufferError, EntryError, TypeError};
 use naga::front::wgsl;
 use thiserror::Error;
 
@@ -14,47 +16,146 @@ use thiserror::Error;
 pub enum ShaderError {
     #[error("No File Name found")]
     NoFileName,
+    #[error("OUT_DIR is not set, shaders must be compiled from a build script")]
+    NoOutDir,
+    #[error("Multiple shaders would generate {0}")]
+    DuplicateModule(String),
     #[error("Failed to read shader file")]
     FailedToReadFile(#[from] std::io::Error),
     #[error("Failed to parse wgsl shader file")]
     WGSLParseError(#[from] wgsl::ParseError),
     #[error("Failed to parse shader entries")]
     EntryError(#[from] EntryError),
+    #[error("Failed to map shader types")]
+    TypeError(#[from] TypeError),
+    #[error("Failed to map shader bindings")]
+    BufferError(#[from] BufferError),
+    #[error("Failed to generate rust code")]
+    CodegenError(#[from] syn::Error),
 }
 
 pub(crate) type Result<T> = std::result::Result<T, ShaderError>;
 
-pub fn compile_shader<T: AsRef<str>>(name: T, shader: impl Into<PathBuf>) -> Result<()> {
-    let file: PathBuf = shader.into();
-    let path = file.clone();
-    let path = path.as_os_str().to_string_lossy();
-    let mut assets_file = PathBuf::from("assets");
-    assets_file.push(file);
-    let rust_file = internals::compile_shader(name, fs::read_to_string(assets_file)?, path)?;
+/// Generates `$OUT_DIR/<shader name>.rs` for a single WGSL file, to be used from a build script
+/// ```ignore
+/// mod shader {
+///     include!(concat!(env!("OUT_DIR"), "/hello.rs"));
+/// }
+/// ```
+pub fn compile_shader(shader: impl AsRef<Path>) -> Result<PathBuf> {
+    ShaderCodegen::new(shader.as_ref()).compile()
+}
+
+/// Generates one module in `$OUT_DIR` for every listed WGSL file
+pub fn compile_shaders<P: Into<PathBuf>>(
+    shaders: impl IntoIterator<Item = P>,
+) -> Result<Vec<PathBuf>> {
+    let shaders: Vec<_> = shaders.into_iter().map(ShaderCodegen::new).collect();
+    check_duplicates(&shaders)?;
+
+    shaders.into_iter().map(ShaderCodegen::compile).collect()
+}
+
+/// Generates one module in `$OUT_DIR` for every `.wgsl` file found under `dir`, such as `assets`
+pub fn compile_shader_dir(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut shaders = vec![];
+    find_shaders(dir, &mut shaders)?;
+    shaders.sort();
 
-    let mut file = File::create("../examples/generated_temp.rs")?;
-    file.write_all(rust_file.as_bytes())?;
+    compile_shaders(shaders)
+}
+
+fn find_shaders(dir: &Path, shaders: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_shaders(&path, shaders)?;
+        } else if path.extension().is_some_and(|ext| ext == "wgsl") {
+            shaders.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_duplicates(shaders: &[ShaderCodegen]) -> Result<()> {
+    let mut names = vec![];
+    for shader in shaders {
+        let name = shader.name()?;
+        if names.contains(&name) {
+            Err(ShaderError::DuplicateModule(format!("{name}.rs")))?
+        }
+        names.push(name);
+    }
 
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::env;
+
     use tracing::debug;
 
     use super::*;
 
+    fn test_out_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("bevy-shader-build-{name}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
     #[test_log::test]
     fn test_invalid_shader_path() {
-        let res = compile_shader("test", "/../foo");
+        let res = ShaderCodegen::new("/../foo")
+            .out_dir(test_out_dir("invalid"))
+            .compile();
 
         assert!(res.is_err());
     }
 
     #[test_log::test]
     fn test_shader_example() {
-        let res = compile_shader("hello", "hello.wgsl");
+        let res = ShaderCodegen::new("assets/hello.wgsl")
+            .out_dir(test_out_dir("hello"))
+            .compile();
         debug!(?res);
-        assert!(res.is_err());
+        assert!(res.is_ok());
+    }
+
+    #[test_log::test]
+    fn test_shader_dir() {
+        let out_dir = test_out_dir("dir");
+        let mut shaders = vec![];
+        find_shaders(
+            Path::new("../examples/hello_world_no_builder/assets"),
+            &mut shaders,
+        )
+        .unwrap();
+        assert_eq!(shaders.len(), 1);
+
+        let res = ShaderCodegen::new(&shaders[0])
+            .out_dir(&out_dir)
+            .compile()
+            .unwrap();
+        assert_eq!(res, out_dir.join("hello.rs"));
+        assert!(
+            fs::read_to_string(res)
+                .unwrap()
+                .contains("pub enum HelloEntries")
+        );
+    }
+
+    #[test_log::test]
+    fn test_duplicate_modules() {
+        let res = check_duplicates(&[
+            ShaderCodegen::new("a/hello.wgsl"),
+            ShaderCodegen::new("b/hello.wgsl"),
+        ]);
+
+        assert!(matches!(res, Err(ShaderError::DuplicateModule(_))));
     }
 }
@@ -0,0 +1,142 @@
+use bevy_shader_helper::internals::prelude::*;
+pub type NarrowFormatsShaderPlugin = ShaderPlugin<
+    NarrowFormatsData,
+    NarrowFormatsEntries,
+    NarrowFormatsBuffers,
+    28,
+    1,
+>;
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum NarrowFormatsEntries {
+    Main,
+}
+#[derive(ShaderDataDetails, Clone)]
+#[entry("main")]
+pub struct NarrowFormatsData {
+    #[texture(WriteOnly, R8Unorm, D1)]
+    pub t_r8unorm: ImageBuilder<R8Unorm, D1>,
+    #[texture(ReadOnly, R8Snorm, D2)]
+    pub t_r8snorm: ImageBuilder<R8Snorm, D2>,
+    #[texture(ReadOnly, R8Uint, D2Array)]
+    pub t_r8uint: ImageBuilder<R8Uint, D2Array>,
+    #[texture(WriteOnly, R8Sint, D3)]
+    pub t_r8sint: ImageBuilder<R8Sint, D3>,
+    #[texture(ReadOnly, Rg8Unorm, D1)]
+    pub t_rg8unorm: ImageBuilder<Rg8Unorm, D1>,
+    #[texture(ReadOnly, Rg8Snorm, D2)]
+    pub t_rg8snorm: ImageBuilder<Rg8Snorm, D2>,
+    #[texture(WriteOnly, Rg8Uint, D2Array)]
+    pub t_rg8uint: ImageBuilder<Rg8Uint, D2Array>,
+    #[texture(ReadOnly, Rg8Sint, D3)]
+    pub t_rg8sint: ImageBuilder<Rg8Sint, D3>,
+    #[texture(ReadOnly, Rgba8Unorm, D1)]
+    pub t_rgba8unorm: ImageBuilder<Rgba8Unorm, D1>,
+    #[texture(WriteOnly, Rgba8Snorm, D2)]
+    pub t_rgba8snorm: ImageBuilder<Rgba8Snorm, D2>,
+    #[texture(ReadOnly, Rgba8Uint, D2Array)]
+    pub t_rgba8uint: ImageBuilder<Rgba8Uint, D2Array>,
+    #[texture(ReadOnly, Rgba8Sint, D3)]
+    pub t_rgba8sint: ImageBuilder<Rgba8Sint, D3>,
+    #[texture(WriteOnly, Bgra8Unorm, D1)]
+    pub t_bgra8unorm: ImageBuilder<Bgra8Unorm, D1>,
+    #[texture(ReadOnly, R16Unorm, D2)]
+    pub t_r16unorm: ImageBuilder<R16Unorm, D2>,
+    #[texture(ReadOnly, R16Snorm, D2Array)]
+    pub t_r16snorm: ImageBuilder<R16Snorm, D2Array>,
+    #[texture(WriteOnly, R16Uint, D3)]
+    pub t_r16uint: ImageBuilder<R16Uint, D3>,
+    #[texture(ReadOnly, R16Sint, D1)]
+    pub t_r16sint: ImageBuilder<R16Sint, D1>,
+    #[texture(ReadOnly, R16Float, D2)]
+    pub t_r16float: ImageBuilder<R16Float, D2>,
+    #[texture(WriteOnly, Rg16Unorm, D2Array)]
+    pub t_rg16unorm: ImageBuilder<Rg16Unorm, D2Array>,
+    #[texture(ReadOnly, Rg16Snorm, D3)]
+    pub t_rg16snorm: ImageBuilder<Rg16Snorm, D3>,
+    #[texture(ReadOnly, Rg16Uint, D1)]
+    pub t_rg16uint: ImageBuilder<Rg16Uint, D1>,
+    #[texture(WriteOnly, Rg16Sint, D2)]
+    pub t_rg16sint: ImageBuilder<Rg16Sint, D2>,
+    #[texture(ReadOnly, Rg16Float, D2Array)]
+    pub t_rg16float: ImageBuilder<Rg16Float, D2Array>,
+    #[texture(ReadOnly, Rgba16Unorm, D3)]
+    pub t_rgba16unorm: ImageBuilder<Rgba16Unorm, D3>,
+    #[texture(WriteOnly, Rgba16Snorm, D1)]
+    pub t_rgba16snorm: ImageBuilder<Rgba16Snorm, D1>,
+    #[texture(ReadOnly, Rgba16Uint, D2)]
+    pub t_rgba16uint: ImageBuilder<Rgba16Uint, D2>,
+    #[texture(ReadOnly, Rgba16Sint, D2Array)]
+    pub t_rgba16sint: ImageBuilder<Rgba16Sint, D2Array>,
+    #[texture(WriteOnly, Rgba16Float, D3)]
+    pub t_rgba16float: ImageBuilder<Rgba16Float, D3>,
+}
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(NarrowFormatsData)]
+pub struct NarrowFormatsBuffers {
+    #[writeable]
+    #[texture]
+    pub t_r8unorm: WriteBuffer<Image>,
+    #[texture]
+    pub t_r8snorm: ReadBuffer<Image>,
+    #[texture]
+    pub t_r8uint: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_r8sint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rg8unorm: ReadBuffer<Image>,
+    #[texture]
+    pub t_rg8snorm: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rg8uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rg8sint: ReadBuffer<Image>,
+    #[texture]
+    pub t_rgba8unorm: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rgba8snorm: WriteBuffer<Image>,
+    #[texture]
+    pub t_rgba8uint: ReadBuffer<Image>,
+    #[texture]
+    pub t_rgba8sint: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_bgra8unorm: WriteBuffer<Image>,
+    #[texture]
+    pub t_r16unorm: ReadBuffer<Image>,
+    #[texture]
+    pub t_r16snorm: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_r16uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_r16sint: ReadBuffer<Image>,
+    #[texture]
+    pub t_r16float: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rg16unorm: WriteBuffer<Image>,
+    #[texture]
+    pub t_rg16snorm: ReadBuffer<Image>,
+    #[texture]
+    pub t_rg16uint: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rg16sint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rg16float: ReadBuffer<Image>,
+    #[texture]
+    pub t_rgba16unorm: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rgba16snorm: WriteBuffer<Image>,
+    #[texture]
+    pub t_rgba16uint: ReadBuffer<Image>,
+    #[texture]
+    pub t_rgba16sint: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rgba16float: WriteBuffer<Image>,
+}
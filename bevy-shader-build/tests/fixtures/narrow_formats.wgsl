@@ -0,0 +1,31 @@
+// Storage texture formats with 8 and 16 bit channels, every storage texture dimension appears at least once, only r32 formats can be read_write
+@group(0) @binding(0) var t_r8unorm: texture_storage_1d<r8unorm, write>;
+@group(0) @binding(1) var t_r8snorm: texture_storage_2d<r8snorm, read>;
+@group(0) @binding(2) var t_r8uint: texture_storage_2d_array<r8uint, read>;
+@group(0) @binding(3) var t_r8sint: texture_storage_3d<r8sint, write>;
+@group(0) @binding(4) var t_rg8unorm: texture_storage_1d<rg8unorm, read>;
+@group(0) @binding(5) var t_rg8snorm: texture_storage_2d<rg8snorm, read>;
+@group(0) @binding(6) var t_rg8uint: texture_storage_2d_array<rg8uint, write>;
+@group(0) @binding(7) var t_rg8sint: texture_storage_3d<rg8sint, read>;
+@group(0) @binding(8) var t_rgba8unorm: texture_storage_1d<rgba8unorm, read>;
+@group(0) @binding(9) var t_rgba8snorm: texture_storage_2d<rgba8snorm, write>;
+@group(0) @binding(10) var t_rgba8uint: texture_storage_2d_array<rgba8uint, read>;
+@group(0) @binding(11) var t_rgba8sint: texture_storage_3d<rgba8sint, read>;
+@group(0) @binding(12) var t_bgra8unorm: texture_storage_1d<bgra8unorm, write>;
+@group(0) @binding(13) var t_r16unorm: texture_storage_2d<r16unorm, read>;
+@group(0) @binding(14) var t_r16snorm: texture_storage_2d_array<r16snorm, read>;
+@group(0) @binding(15) var t_r16uint: texture_storage_3d<r16uint, write>;
+@group(0) @binding(16) var t_r16sint: texture_storage_1d<r16sint, read>;
+@group(0) @binding(17) var t_r16float: texture_storage_2d<r16float, read>;
+@group(0) @binding(18) var t_rg16unorm: texture_storage_2d_array<rg16unorm, write>;
+@group(0) @binding(19) var t_rg16snorm: texture_storage_3d<rg16snorm, read>;
+@group(0) @binding(20) var t_rg16uint: texture_storage_1d<rg16uint, read>;
+@group(0) @binding(21) var t_rg16sint: texture_storage_2d<rg16sint, write>;
+@group(0) @binding(22) var t_rg16float: texture_storage_2d_array<rg16float, read>;
+@group(0) @binding(23) var t_rgba16unorm: texture_storage_3d<rgba16unorm, read>;
+@group(0) @binding(24) var t_rgba16snorm: texture_storage_1d<rgba16snorm, write>;
+@group(0) @binding(25) var t_rgba16uint: texture_storage_2d<rgba16uint, read>;
+@group(0) @binding(26) var t_rgba16sint: texture_storage_2d_array<rgba16sint, read>;
+@group(0) @binding(27) var t_rgba16float: texture_storage_3d<rgba16float, write>;
+
+@compute @workgroup_size(1) fn main() {}
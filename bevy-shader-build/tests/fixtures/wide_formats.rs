@@ -0,0 +1,73 @@
+use bevy_shader_helper::internals::prelude::*;
+pub type WideFormatsShaderPlugin = ShaderPlugin<
+    WideFormatsData,
+    WideFormatsEntries,
+    WideFormatsBuffers,
+    12,
+    1,
+>;
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum WideFormatsEntries {
+    Main,
+}
+#[derive(ShaderDataDetails, Clone)]
+#[entry("main")]
+pub struct WideFormatsData {
+    #[texture(WriteOnly, R32Uint, D1)]
+    pub t_r32uint: ImageBuilder<R32Uint, D1>,
+    #[texture(ReadOnly, R32Sint, D2)]
+    pub t_r32sint: ImageBuilder<R32Sint, D2>,
+    #[texture(ReadWrite, R32Float, D2Array)]
+    pub t_r32float: ImageBuilder<R32Float, D2Array>,
+    #[texture(WriteOnly, Rg32Uint, D3)]
+    pub t_rg32uint: ImageBuilder<Rg32Uint, D3>,
+    #[texture(ReadOnly, Rg32Sint, D1)]
+    pub t_rg32sint: ImageBuilder<Rg32Sint, D1>,
+    #[texture(ReadOnly, Rg32Float, D2)]
+    pub t_rg32float: ImageBuilder<Rg32Float, D2>,
+    #[texture(WriteOnly, Rgba32Uint, D2Array)]
+    pub t_rgba32uint: ImageBuilder<Rgba32Uint, D2Array>,
+    #[texture(ReadOnly, Rgba32Sint, D3)]
+    pub t_rgba32sint: ImageBuilder<Rgba32Sint, D3>,
+    #[texture(ReadOnly, Rgba32Float, D1)]
+    pub t_rgba32float: ImageBuilder<Rgba32Float, D1>,
+    #[texture(WriteOnly, Rgb10a2Uint, D2)]
+    pub t_rgb10a2uint: ImageBuilder<Rgb10a2Uint, D2>,
+    #[texture(ReadOnly, Rgb10a2Unorm, D2Array)]
+    pub t_rgb10a2unorm: ImageBuilder<Rgb10a2Unorm, D2Array>,
+    #[texture(ReadOnly, Rg11b10Ufloat, D3)]
+    pub t_rg11b10float: ImageBuilder<Rg11b10Ufloat, D3>,
+}
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(WideFormatsData)]
+pub struct WideFormatsBuffers {
+    #[writeable]
+    #[texture]
+    pub t_r32uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_r32sint: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_r32float: ReadWriteBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rg32uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rg32sint: ReadBuffer<Image>,
+    #[texture]
+    pub t_rg32float: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rgba32uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rgba32sint: ReadBuffer<Image>,
+    #[texture]
+    pub t_rgba32float: ReadBuffer<Image>,
+    #[writeable]
+    #[texture]
+    pub t_rgb10a2uint: WriteBuffer<Image>,
+    #[texture]
+    pub t_rgb10a2unorm: ReadBuffer<Image>,
+    #[texture]
+    pub t_rg11b10float: ReadBuffer<Image>,
+}
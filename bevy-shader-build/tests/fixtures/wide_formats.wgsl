@@ -0,0 +1,15 @@
+// Storage texture formats with 32 bit channels, and packed formats, every storage texture dimension appears at least once, only r32 formats can be read_write
+@group(0) @binding(0) var t_r32uint: texture_storage_1d<r32uint, write>;
+@group(0) @binding(1) var t_r32sint: texture_storage_2d<r32sint, read>;
+@group(0) @binding(2) var t_r32float: texture_storage_2d_array<r32float, read_write>;
+@group(0) @binding(3) var t_rg32uint: texture_storage_3d<rg32uint, write>;
+@group(0) @binding(4) var t_rg32sint: texture_storage_1d<rg32sint, read>;
+@group(0) @binding(5) var t_rg32float: texture_storage_2d<rg32float, read>;
+@group(0) @binding(6) var t_rgba32uint: texture_storage_2d_array<rgba32uint, write>;
+@group(0) @binding(7) var t_rgba32sint: texture_storage_3d<rgba32sint, read>;
+@group(0) @binding(8) var t_rgba32float: texture_storage_1d<rgba32float, read>;
+@group(0) @binding(9) var t_rgb10a2uint: texture_storage_2d<rgb10a2uint, write>;
+@group(0) @binding(10) var t_rgb10a2unorm: texture_storage_2d_array<rgb10a2unorm, read>;
+@group(0) @binding(11) var t_rg11b10float: texture_storage_3d<rg11b10float, read>;
+
+@compute @workgroup_size(1) fn main() {}
@@ -0,0 +1,146 @@
+//! Compiles the code generated from the WGSL fixtures, so every generated binding has to build
+//! against the helper. Run with `BLESS=1` to rewrite the `.rs` fixtures after changing codegen
+
+use bevy_shader_helper::{
+    ImageBuilder, ShaderBuilder,
+    bevy::render::render_resource::{
+        BindGroupLayoutEntry, BindingType, Extent3d, ShaderStages, TextureViewDimension,
+    },
+    internals::prelude::{CpuExecutor, ShaderDataDetails, ShaderEntry, ShaderSource},
+};
+
+// Each `BufferGroup` derive imports `bevy_ecs`, so every fixture lives in its own module
+mod narrow_formats {
+    include!("fixtures/narrow_formats.rs");
+}
+mod wide_formats {
+    include!("fixtures/wide_formats.rs");
+}
+
+// The fixtures are the formatted output
+#[cfg(feature = "format")]
+#[test]
+fn test_fixtures_match_codegen() {
+    use std::{env, fs};
+
+    use bevy_shader_build::ShaderCodegen;
+
+    let out_dir = env::temp_dir().join("bevy-shader-build-fixtures");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    for name in ["narrow_formats", "wide_formats"] {
+        let generated = ShaderCodegen::new(format!("tests/fixtures/{name}.wgsl"))
+            .out_dir(&out_dir)
+            .compile()
+            .unwrap();
+        let generated = fs::read_to_string(generated).unwrap();
+        let fixture = format!("tests/fixtures/{name}.rs");
+        if env::var_os("BLESS").is_some() {
+            fs::write(&fixture, &generated).unwrap();
+        }
+
+        assert_eq!(
+            generated,
+            fs::read_to_string(&fixture).unwrap(),
+            "{fixture} is outdated, rerun with BLESS=1"
+        );
+    }
+}
+
+fn image<F, D>() -> ImageBuilder<F, D> {
+    Extent3d {
+        width: 2,
+        height: 1,
+        depth_or_array_layers: 1,
+    }
+    .into()
+}
+
+#[test]
+fn test_generated_textures() {
+    use narrow_formats::{NarrowFormatsData, NarrowFormatsEntries, NarrowFormatsShaderPlugin};
+    use wide_formats::{WideFormatsData, WideFormatsEntries, WideFormatsShaderPlugin};
+
+    let narrow: NarrowFormatsShaderPlugin = ShaderBuilder::default()
+        .initial_data(NarrowFormatsData {
+            t_r8unorm: image(),
+            t_r8snorm: image(),
+            t_r8uint: image(),
+            t_r8sint: image(),
+            t_rg8unorm: image(),
+            t_rg8snorm: image(),
+            t_rg8uint: image(),
+            t_rg8sint: image(),
+            t_rgba8unorm: image(),
+            t_rgba8snorm: image(),
+            t_rgba8uint: image(),
+            t_rgba8sint: image(),
+            t_bgra8unorm: image(),
+            t_r16unorm: image(),
+            t_r16snorm: image(),
+            t_r16uint: image(),
+            t_r16sint: image(),
+            t_r16float: image(),
+            t_rg16unorm: image(),
+            t_rg16snorm: image(),
+            t_rg16uint: image(),
+            t_rg16sint: image(),
+            t_rg16float: image(),
+            t_rgba16unorm: image(),
+            t_rgba16snorm: image(),
+            t_rgba16uint: image(),
+            t_rgba16sint: image(),
+            t_rgba16float: image(),
+        })
+        .shader(ShaderSource::wgsl(include_str!(
+            "fixtures/narrow_formats.wgsl"
+        )))
+        .build();
+    let wide: WideFormatsShaderPlugin = ShaderBuilder::default()
+        .initial_data(WideFormatsData {
+            t_r32uint: image(),
+            t_r32sint: image(),
+            t_r32float: image(),
+            t_rg32uint: image(),
+            t_rg32sint: image(),
+            t_rg32float: image(),
+            t_rgba32uint: image(),
+            t_rgba32sint: image(),
+            t_rgba32float: image(),
+            t_rgb10a2uint: image(),
+            t_rgb10a2unorm: image(),
+            t_rg11b10float: image(),
+        })
+        .shader(ShaderSource::wgsl(include_str!(
+            "fixtures/wide_formats.wgsl"
+        )))
+        .build();
+    // Creating the executor creates every image, and the buffer group holding them
+    CpuExecutor::new(narrow).unwrap();
+    CpuExecutor::new(wide).unwrap();
+    assert_eq!(NarrowFormatsEntries::Main.name(), "Main");
+    assert_eq!(WideFormatsEntries::Main.name(), "Main");
+
+    let views = |entries: &[BindGroupLayoutEntry]| -> Vec<_> {
+        entries
+            .iter()
+            .map(|entry| match entry.ty {
+                BindingType::StorageTexture { view_dimension, .. } => view_dimension,
+                _ => unreachable!(),
+            })
+            .collect()
+    };
+    let narrow = NarrowFormatsData::buffer_entries(ShaderStages::COMPUTE);
+    let wide = WideFormatsData::buffer_entries(ShaderStages::COMPUTE);
+    assert_eq!(narrow.len(), 28);
+    assert_eq!(
+        views(&wide),
+        [
+            TextureViewDimension::D1,
+            TextureViewDimension::D2,
+            TextureViewDimension::D2Array,
+            TextureViewDimension::D3,
+        ]
+        .repeat(3)
+    );
+}
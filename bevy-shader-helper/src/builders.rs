@@ -2,9 +2,13 @@ use std::marker::PhantomData;
 
 use bevy_asset::RenderAssetUsages;
 use bevy_image::Image;
-use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor,
+};
 
+use crate::internals::compute::HotReload;
 use crate::internals::entries::{Dispatch, Entry};
+use crate::internals::source::ShaderSource;
 use crate::texture_details::{ToTextureDimension, ToTextureFormat};
 
 #[derive(Clone, Default)]
@@ -17,7 +21,8 @@ pub enum ImageData {
 
 impl ImageData {
     fn image(self, size: Extent3d, format: TextureFormat, dimension: TextureDimension) -> Image {
-        let asset_usage = RenderAssetUsages::RENDER_WORLD;
+        // Kept in the main world, where writes and readbacks of the image look it up
+        let asset_usage = RenderAssetUsages::default();
         match self {
             ImageData::Fill(data) => Image::new_fill(size, dimension, &data, format, asset_usage),
             ImageData::Data(vec) => Image::new(size, dimension, vec, format, asset_usage),
@@ -55,11 +60,39 @@ impl<F, D> Clone for ImageBuilder<F, D> {
     }
 }
 
+impl<F: ToTextureFormat, D> ImageBuilder<F, D> {
+    /// Sets the texels in row order, there must be one for every texel of `size`
+    /// ```ignore
+    /// let image: ImageBuilder<Rgba32Float, D2> = Extent3d { width: 2, height: 1, ..default() }.into();
+    /// image.pixels([[1., 0., 0., 1.], [0., 1., 0., 1.]])
+    /// ```
+    pub fn pixels(mut self, pixels: impl AsRef<[F::Pixel]>) -> Self {
+        self.data = ImageData::Data(bytemuck::cast_slice(pixels.as_ref()).to_vec());
+
+        self
+    }
+
+    /// Sets every texel to `pixel`
+    pub fn fill(mut self, pixel: F::Pixel) -> Self {
+        let texels = self.size.width * self.size.height * self.size.depth_or_array_layers;
+        self.data = ImageData::Data(bytemuck::bytes_of(&pixel).repeat(texels as usize));
+
+        self
+    }
+}
+
 impl<F: ToTextureFormat, D: ToTextureDimension> From<ImageBuilder<F, D>> for Image {
     fn from(val: ImageBuilder<F, D>) -> Self {
         let dimension = D::texture_dimension();
         let format = F::texture_format();
-        val.data.image(val.size, format, dimension)
+        let mut image = val.data.image(val.size, format, dimension);
+        // A single layer texture is viewed as D2 unless the view asks for an array
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(D::view_dimension()),
+            ..Default::default()
+        });
+
+        image
     }
 }
 
@@ -77,6 +110,9 @@ impl<F, D> From<Extent3d> for ImageBuilder<F, D> {
 pub struct ShaderBuilder<T: ?Sized, DataTy, EntriesTy> {
     pub(crate) initial_data: Option<DataTy>,
     pub(crate) dispatches: Option<Dispatch<EntriesTy>>,
+    pub(crate) shader: Option<ShaderSource>,
+    pub(crate) hot_reload: HotReload,
+    pub(crate) gpu_timings: bool,
     _phantom: PhantomData<T>,
 }
 
@@ -87,6 +123,9 @@ impl<DataTy, EntriesTy, T: BuildableShader<DataTy, EntriesTy>> Default
         Self {
             initial_data: Default::default(),
             dispatches: Default::default(),
+            shader: Default::default(),
+            hot_reload: Default::default(),
+            gpu_timings: false,
             _phantom: Default::default(),
         }
     }
@@ -99,6 +138,31 @@ impl<DataTy, EntriesTy, T: BuildableShader<DataTy, EntriesTy>> ShaderBuilder<T,
         self
     }
 
+    /// Overrides the `#[shader(..)]` source of the data type, accepts asset paths and handles
+    pub fn shader(mut self, source: impl Into<ShaderSource>) -> Self {
+        self.shader = Some(source.into());
+
+        self
+    }
+
+    /// How the shader behaves when its source changes while the app is running
+    pub fn hot_reload(mut self, hot_reload: HotReload) -> Self {
+        self.hot_reload = hot_reload;
+
+        self
+    }
+
+    /// Measures how long every dispatched entry takes, published to the `DiagnosticsStore` as
+    /// `render/<entry variant>/elapsed_gpu`
+    ///
+    /// Nothing is recorded unless the app also adds the `RenderDiagnosticsPlugin`. The GPU time needs
+    /// `TIMESTAMP_QUERY_INSIDE_PASSES`, otherwise only `render/<entry variant>/elapsed_cpu` is measured
+    pub fn gpu_timings(mut self, enabled: bool) -> Self {
+        self.gpu_timings = enabled;
+
+        self
+    }
+
     pub fn on_startup<E: Into<Vec<Entry<EntriesTy>>>>(mut self, entries: E) -> Self {
         let dispatch = match self.dispatches {
             Some(mut dispatch) => {
@@ -108,6 +172,7 @@ impl<DataTy, EntriesTy, T: BuildableShader<DataTy, EntriesTy>> ShaderBuilder<T,
             None => Dispatch {
                 on_startup: entries.into(),
                 on_update: vec![],
+                on_request: vec![],
             },
         };
 
@@ -124,6 +189,7 @@ impl<DataTy, EntriesTy, T: BuildableShader<DataTy, EntriesTy>> ShaderBuilder<T,
             None => Dispatch {
                 on_startup: vec![],
                 on_update: entries.into(),
+                on_request: vec![],
             },
         };
 
@@ -1,16 +1,33 @@
 pub mod binding;
 pub mod buffers;
 pub mod compute;
+pub mod control;
 pub mod entries;
+pub mod interpreter;
 pub mod label;
 pub mod pipeline;
 pub mod plugin;
+pub mod readback;
+pub mod runner;
+pub mod schedule;
+pub mod source;
+pub mod status;
+pub mod workgroups;
 
 pub mod prelude {
     pub use super::binding::ShaderDataDetails;
+    pub use super::compute::HotReload;
+    pub use super::control::ShaderControl;
     pub use super::buffers::*;
-    pub use super::entries::ShaderEntry;
+    pub use super::entries::{DispatchShader, Entry, ShaderEntry};
+    pub use super::interpreter::CpuExecutor;
     pub use super::plugin::ShaderPlugin;
+    pub use super::readback::{OnReadback, ReadbackData};
+    pub use super::runner::{ComputeResults, ComputeRunner, RunnerError};
+    pub use super::schedule::{DispatchSchedule, RunCondition};
+    pub use super::source::ShaderSource;
+    pub use super::status::{ShaderError, ShaderStatus};
+    pub use super::workgroups::Workgroups;
     pub use crate::ImageBuilder;
     pub use crate::texture_details::*;
 
@@ -1,7 +1,10 @@
 use std::{borrow::Cow, marker::PhantomData};
 
 use bevy_asset::Handle;
-use bevy_ecs::system::{Commands, Res, Resource};
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    system::{Commands, Res, Resource},
+};
 use bevy_render::{
     render_asset::RenderAssets,
     render_resource::{
@@ -13,32 +16,57 @@ use bevy_render::{
     texture::GpuImage,
 };
 
-use super::{buffers::BufferGroup, pipeline::Pipeline};
+use super::{
+    buffers::{BufferGroup, ResourceId},
+    pipeline::Pipeline,
+    source::ShaderSource,
+};
 
 pub use bevy_shader_macros::ShaderDataDetails;
 pub trait ShaderDataDetails<const B: usize, const E: usize> {
     fn buffer_entries(stage: ShaderStages) -> BindGroupLayoutEntries<B>;
 
+    /// The bind group of every entry in `buffer_entries`
+    fn groups() -> [u32; B] {
+        [0; B]
+    }
+
+    /// Bytes of one element of every entry in `buffer_entries`, items of array fields are elements
+    fn element_sizes() -> [u64; B];
+
+    /// The source set with `#[shader(..)]`, used when the builder does not set one
+    fn shader() -> Option<ShaderSource> {
+        None
+    }
+
     fn bind_group_label() -> Option<&'static str> {
         None
     }
 
+    /// The entry point name of every pipeline in `entries`
+    fn entry_names() -> [&'static str; E];
+
+    /// The label set with `#[entry("name", "label")]` of every pipeline in `entries`
+    fn entry_labels() -> [Option<&'static str>; E] {
+        [None; E]
+    }
+
     fn entries(
         pipeline_cache: &PipelineCache,
-        layout: BindGroupLayout,
+        layouts: &[BindGroupLayout],
         shader: Handle<Shader>,
     ) -> [CachedComputePipelineId; E];
 
     fn create_entry(
         pipeline_cache: &PipelineCache,
-        layout: BindGroupLayout,
+        layouts: &[BindGroupLayout],
         shader: Handle<Shader>,
         entry: &'static str,
         label: Option<Cow<'static, str>>,
     ) -> CachedComputePipelineId {
         pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
             label,
-            layout: vec![layout],
+            layout: layouts.to_vec(),
             push_constant_ranges: vec![],
             shader,
             shader_defs: vec![],
@@ -60,23 +88,83 @@ pub(super) fn prepare_bind_group<
     buffer: Res<BuffersTy>,
     buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
     images: Res<RenderAssets<GpuImage>>,
+    bind_group: Option<Res<GenericBindGroup<PipelineTy>>>,
 ) {
+    let unchanged = !(buffer.is_changed() || buffers.is_changed() || images.is_changed());
+    if bind_group.is_some() && unchanged {
+        return;
+    }
+    // Handles can be swapped or assets re-uploaded, which only shows up as different GPU resources
+    let resources = buffer.resource_ids(&buffers, &images);
+    if resources.iter().any(Option::is_none)
+        || bind_group.is_some_and(|bind_group| bind_group.resources == resources)
+    {
+        return;
+    }
+
     // debug!("Preparing bind group");
-    let bind_group = render_device.create_bind_group(
-        BuffersTy::label(),
-        pipeline.layout(),
-        &buffer.get_bindings(&buffers, &images),
-    );
+    let sets = if BuffersTy::ping_pong() {
+        vec![false, true]
+    } else {
+        vec![false]
+    };
+    let groups = BuffersTy::groups();
+    let sets = sets
+        .into_iter()
+        .map(|swapped| {
+            let bindings = buffer.get_bindings(&buffers, &images, swapped);
+            pipeline
+                .layouts()
+                .iter()
+                .enumerate()
+                .map(|(group, layout)| {
+                    let entries: Vec<_> =
+                        group_entries(&bindings, &groups, group).cloned().collect();
+                    render_device.create_bind_group(BuffersTy::label(), layout, &entries)
+                })
+                .collect()
+        })
+        .collect();
 
-    let bind_group: GenericBindGroup<PipelineTy> = GenericBindGroup::from_bind_group(bind_group);
+    let bind_group: GenericBindGroup<PipelineTy> = GenericBindGroup {
+        sets,
+        resources,
+        _phantom: PhantomData,
+    };
     commands.insert_resource(bind_group);
 }
 
+/// Number of bind groups needed to cover every group index, groups without bindings stay empty
+pub(super) fn group_count(groups: &[u32]) -> usize {
+    groups.iter().max().map_or(1, |group| *group as usize + 1)
+}
+
+/// The entries of a flattened binding list which belong to `group`
+pub(super) fn group_entries<'a, T>(
+    entries: &'a [T],
+    groups: &'a [u32],
+    group: usize,
+) -> impl Iterator<Item = &'a T> {
+    entries
+        .iter()
+        .zip(groups)
+        .filter(move |(_, g)| **g as usize == group)
+        .map(|(entry, _)| entry)
+}
+
 #[derive(Resource)]
-pub(super) struct GenericBindGroup<T>(pub(super) render_resource::BindGroup, PhantomData<T>);
+pub(super) struct GenericBindGroup<T> {
+    /// One bind group per group index, and a second set with swapped halves for ping pong buffers
+    sets: Vec<Vec<render_resource::BindGroup>>,
+    /// What the bind groups were created from
+    resources: Vec<Option<ResourceId>>,
+    _phantom: PhantomData<T>,
+}
 
 impl<T> GenericBindGroup<T> {
-    fn from_bind_group(bind_group: render_resource::BindGroup) -> Self {
-        Self(bind_group, Default::default())
+    /// The bind groups reading the `front` ping pong halves, groups without ping pong buffers only
+    /// have one set
+    pub(super) fn current(&self, front: usize) -> &[render_resource::BindGroup] {
+        &self.sets[front.min(self.sets.len() - 1)]
     }
 }
@@ -1,18 +1,29 @@
+use std::{fmt, marker::PhantomData};
+
 use bevy_app::App;
 use bevy_asset::{Asset, Assets, Handle};
-use bevy_ecs::system::{Commands, ResMut};
+use bevy_ecs::{
+    component::Component,
+    query::QueryItem,
+    system::{Commands, Query, Res, ResMut, Resource},
+};
 use bevy_image::Image;
+use bevy_math::UVec3;
 use bevy_render::{
+    MainWorld,
+    extract_component::ExtractComponent,
     extract_resource::{ExtractResource, ExtractResourcePlugin},
     gpu_readback::Readback,
-    render_asset::RenderAssets,
+    render_asset::{RenderAssetUsages, RenderAssets},
     render_resource::{
-        BindGroupEntries, BindingResource, BufferUsages, IntoBinding, ShaderType, TextureUsages,
-        encase::internal::WriteInto,
+        BindGroupEntries, BindingResource, Buffer, BufferId, BufferUsages, IntoBinding, ShaderSize,
+        ShaderType, TextureFormat, TextureUsages, TextureViewId,
+        encase::{self, internal::WriteInto},
     },
     storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
     texture::GpuImage,
 };
+use bytemuck::Pod;
 
 pub use bevy_shader_macros::BufferGroup;
 pub trait BufferGroup<DataTy: Clone, const B: usize> {
@@ -28,12 +39,68 @@ pub trait BufferGroup<DataTy: Clone, const B: usize> {
         app.add_plugins((ExtractResourcePlugin::<Self>::default(),));
     }
 
+    /// `swapped` selects which half of every [`PingPong`] is the input
     fn get_bindings<'a>(
         &'a self,
         buffers: &'a RenderAssets<GpuShaderStorageBuffer>,
         images: &'a RenderAssets<GpuImage>,
+        swapped: bool,
     ) -> BindGroupEntries<'a, B>; // TODO: consider refactoring the buffer inserters
 
+    /// The resources `get_bindings` would bind, used to only rebuild the bind groups when they change
+    fn resource_ids(
+        &self,
+        buffers: &RenderAssets<GpuShaderStorageBuffer>,
+        images: &RenderAssets<GpuImage>,
+    ) -> Vec<Option<ResourceId>>;
+
+    /// The size of every resource `get_bindings` would bind, used to size dynamic workgroup counts
+    fn extents(
+        &self,
+        buffers: &RenderAssets<GpuShaderStorageBuffer>,
+        images: &RenderAssets<GpuImage>,
+    ) -> Vec<Option<BindingExtent>>;
+
+    /// The asset behind every entry `get_bindings` would bind, read by the [`CpuExecutor`](super::interpreter::CpuExecutor)
+    fn assets(&self, swapped: bool) -> Vec<BindingAsset>;
+
+    /// A readback of every `#[writeable]` field with its group and binding, ping pong fields come
+    /// with the [`PingPongReadback`] which keeps them on their most recently written half
+    fn readbacks(&self) -> Vec<BindingReadback<Self>>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+
+    /// The group and binding of every `#[indirect]` buffer
+    fn indirect_bindings() -> Vec<(u32, u32)> {
+        vec![]
+    }
+
+    /// The size in bytes the initial data gives every entry in `indirect_bindings`
+    fn indirect_sizes(_data: &DataTy) -> Vec<u64> {
+        vec![]
+    }
+
+    /// The GPU buffer behind every entry in `indirect_bindings`
+    fn indirect_buffers(
+        &self,
+        _buffers: &RenderAssets<GpuShaderStorageBuffer>,
+    ) -> Vec<Option<Buffer>> {
+        vec![]
+    }
+
+    /// Whether the group has any [`PingPong`], which all swap at the same time
+    fn ping_pong() -> bool {
+        false
+    }
+
+    /// The bind group of every entry in `get_bindings`
+    fn groups() -> [u32; B] {
+        [0; B]
+    }
+
     fn insert_resources(
         commands: &mut Commands,
         buffers: &mut Assets<ShaderStorageBuffer>,
@@ -54,12 +121,166 @@ pub fn create_storage_buffer<DataTy: ShaderType + WriteInto>(
     buffers.add(data)
 }
 
+/// A storage buffer which also holds the arguments of indirect dispatches, see
+/// [`Workgroups::Indirect`](super::workgroups::Workgroups::Indirect)
+pub fn create_indirect_buffer<DataTy: ShaderType + WriteInto>(
+    buffers: &mut Assets<ShaderStorageBuffer>,
+    data: DataTy,
+) -> Handle<ShaderStorageBuffer> {
+    let mut data = ShaderStorageBuffer::from(data);
+    // Only ever the source of the copy into the argument buffer, never dispatched from directly
+    data.buffer_description.usage |= BufferUsages::COPY_SRC;
+    buffers.add(data)
+}
+
+pub fn create_uniform_buffer<DataTy: ShaderType + WriteInto>(
+    buffers: &mut Assets<ShaderStorageBuffer>,
+    data: DataTy,
+) -> Handle<ShaderStorageBuffer> {
+    let mut buffer = ShaderStorageBuffer::new(&uniform_bytes(data), RenderAssetUsages::default());
+    buffer.buffer_description.usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+    buffers.add(buffer)
+}
+
+// Uniforms have stricter layout rules than storage buffers, so they cannot use ShaderStorageBuffer::set_data
+fn uniform_bytes<DataTy: ShaderType + WriteInto>(data: DataTy) -> Vec<u8> {
+    let mut buffer = encase::UniformBuffer::new(Vec::with_capacity(data.size().get() as usize));
+    buffer
+        .write(&data)
+        .expect("Failed to write uniform buffer data");
+    buffer.into_inner()
+}
+
+/// Why a CPU side write into a buffer or image asset was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteError {
+    /// The handle does not point to a loaded asset
+    MissingAsset,
+    /// The buffer keeps no CPU data to write into, ranges can only be written into buffers created from data
+    NoCpuData,
+    /// The written bytes `start..end` do not fit into the `len` bytes of the asset
+    OutOfRange {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+    /// The texel type does not match the texel size of the image format
+    TexelSize { expected: usize, actual: usize },
+    /// The format has no single texel size, like combined depth stencil formats
+    UnsupportedFormat(TextureFormat),
+    /// The data could not be encoded with the storage buffer layout
+    Encode(String),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAsset => write!(f, "The asset is missing"),
+            Self::NoCpuData => write!(f, "Cannot write a range of a buffer without CPU data"),
+            Self::OutOfRange { start, end, len } => {
+                write!(
+                    f,
+                    "Writing bytes {start}..{end} is out of range for {len} bytes"
+                )
+            }
+            Self::TexelSize { expected, actual } => write!(
+                f,
+                "Texels of the image format are {expected} bytes, but the data has {actual} bytes per texel"
+            ),
+            Self::UnsupportedFormat(format) => write!(f, "Cannot write texels of {format:?}"),
+            Self::Encode(message) => write!(f, "Failed to encode the data: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+fn asset_mut<'a, T: Asset>(
+    assets: &'a mut Assets<T>,
+    handle: &Handle<T>,
+) -> Result<&'a mut T, WriteError> {
+    assets.get_mut(handle).ok_or(WriteError::MissingAsset)
+}
+
+// The storage layout of an array, every element takes `bytes.len() / data.len()` bytes including its padding
+fn array_bytes<DataTy: ShaderType + ShaderSize + WriteInto>(
+    data: &[DataTy],
+) -> Result<Vec<u8>, WriteError> {
+    let mut buffer = encase::StorageBuffer::new(Vec::with_capacity(data.size().get() as usize));
+    buffer
+        .write(data)
+        .map_err(|e| WriteError::Encode(e.to_string()))?;
+    Ok(buffer.into_inner())
+}
+
+fn write_bytes_at(target: &mut [u8], start: usize, bytes: &[u8]) -> Result<(), WriteError> {
+    let end = start + bytes.len();
+    let len = target.len();
+    target
+        .get_mut(start..end)
+        .ok_or(WriteError::OutOfRange { start, end, len })?
+        .copy_from_slice(bytes);
+    Ok(())
+}
+
+fn write_storage_range<DataTy: ShaderType + ShaderSize + WriteInto>(
+    buffer: &mut ShaderStorageBuffer,
+    array_offset: usize,
+    offset: usize,
+    data: &[DataTy],
+) -> Result<(), WriteError> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    let target = buffer.data.as_mut().ok_or(WriteError::NoCpuData)?;
+    let bytes = array_bytes(data)?;
+    let stride = bytes.len() / data.len();
+    write_bytes_at(target, array_offset + offset * stride, &bytes)
+}
+
+// Texels are tightly packed in the image data, unlike the padded elements of storage arrays
+fn texel_bytes<TexelTy: Pod>(image: &Image, data: &[TexelTy]) -> Result<Vec<u8>, WriteError> {
+    let format = image.texture_descriptor.format;
+    let expected = format
+        .block_copy_size(None)
+        .ok_or(WriteError::UnsupportedFormat(format))? as usize;
+    let actual = size_of::<TexelTy>();
+    if expected != actual {
+        return Err(WriteError::TexelSize { expected, actual });
+    }
+    Ok(bytemuck::cast_slice(data).to_vec())
+}
+
+fn set_texels<TexelTy: Pod>(image: &mut Image, data: &[TexelTy]) -> Result<(), WriteError> {
+    let bytes = texel_bytes(image, data)?;
+    if bytes.len() != image.data.len() {
+        return Err(WriteError::OutOfRange {
+            start: 0,
+            end: bytes.len(),
+            len: image.data.len(),
+        });
+    }
+    image.data = bytes;
+    Ok(())
+}
+
+fn write_texels<TexelTy: Pod>(
+    image: &mut Image,
+    offset: usize,
+    data: &[TexelTy],
+) -> Result<(), WriteError> {
+    let bytes = texel_bytes(image, data)?;
+    write_bytes_at(&mut image.data, offset * size_of::<TexelTy>(), &bytes)
+}
+
 pub fn create_texture_buffer(
     images: &mut Assets<Image>,
     image: impl Into<Image>,
     writeable: bool,
 ) -> Handle<Image> {
     let mut image: Image = image.into();
+    // Writes from main world systems need the image to outlive its first extract
+    image.asset_usage |= RenderAssetUsages::MAIN_WORLD;
     image.texture_descriptor.usage |= TextureUsages::STORAGE_BINDING;
     if writeable {
         image.texture_descriptor.usage |= TextureUsages::COPY_SRC;
@@ -92,6 +313,11 @@ impl ReadableBuffer for WriteBuffer<ShaderStorageBuffer> {
         Readback::Buffer(self.handle.clone())
     }
 }
+impl ReadableBuffer for WriteBuffer<Image> {
+    fn readback(&self) -> Readback {
+        Readback::Texture(self.handle.clone())
+    }
+}
 
 pub struct ReadBuffer<T: Asset> {
     pub handle: Handle<T>,
@@ -108,15 +334,79 @@ impl<T: Asset> From<Handle<T>> for ReadBuffer<T> {
         Self { handle: data }
     }
 }
+impl ReadBuffer<ShaderStorageBuffer> {
+    /// Replaces the buffer contents, the next dispatch reads the new value
+    pub fn set<DataTy: ShaderType + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        data: DataTy,
+    ) -> Result<(), WriteError> {
+        asset_mut(buffers, &self.handle)?.set_data(data);
+        Ok(())
+    }
+
+    /// Overwrites the elements starting at `offset` of a buffer that holds only an array
+    pub fn write_range<DataTy: ShaderType + ShaderSize + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        offset: usize,
+        data: &[DataTy],
+    ) -> Result<(), WriteError> {
+        self.write_field_range(buffers, 0, offset, data)
+    }
+
+    /// Overwrites the elements starting at `offset` of an array that starts `array_offset` bytes into the buffer,
+    /// like the runtime sized array at the end of a struct
+    pub fn write_field_range<DataTy: ShaderType + ShaderSize + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        array_offset: usize,
+        offset: usize,
+        data: &[DataTy],
+    ) -> Result<(), WriteError> {
+        let buffer = asset_mut(buffers, &self.handle)?;
+        write_storage_range(buffer, array_offset, offset, data)
+    }
+}
+impl ReadBuffer<Image> {
+    /// Replaces every texel of the image, `data` must cover the whole image
+    /// with one element per texel of the image format, like `[u8; 4]` for `Rgba8Unorm`
+    pub fn set<TexelTy: Pod>(
+        &self,
+        images: &mut Assets<Image>,
+        data: &[TexelTy],
+    ) -> Result<(), WriteError> {
+        set_texels(asset_mut(images, &self.handle)?, data)
+    }
+
+    /// Overwrites the texels starting at `offset`, counted in row order
+    pub fn write_range<TexelTy: Pod>(
+        &self,
+        images: &mut Assets<Image>,
+        offset: usize,
+        data: &[TexelTy],
+    ) -> Result<(), WriteError> {
+        write_texels(asset_mut(images, &self.handle)?, offset, data)
+    }
+}
 impl WriteableBuffer for ReadBuffer<ShaderStorageBuffer> {
     type T = ShaderStorageBuffer;
 
-    fn get_mut<'a>(&'a self, _buffers: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
+    fn get_mut<'a>(&'a self, buffers: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
+    where
+        Self::T: Asset,
+    {
+        buffers.get_mut(&self.handle).expect("Missing Read Buffer")
+    }
+}
+impl WriteableBuffer for ReadBuffer<Image> {
+    type T = Image;
+
+    fn get_mut<'a>(&'a self, images: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
     where
         Self::T: Asset,
     {
-        let _ = self.handle;
-        todo!()
+        images.get_mut(&self.handle).expect("Missing Read Image")
     }
 }
 
@@ -145,6 +435,254 @@ impl ReadableBuffer for ReadWriteBuffer<Image> {
         Readback::Texture(self.handle.clone())
     }
 }
+impl ReadWriteBuffer<ShaderStorageBuffer> {
+    /// Replaces the buffer contents, the next dispatch reads the new value
+    pub fn set<DataTy: ShaderType + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        data: DataTy,
+    ) -> Result<(), WriteError> {
+        asset_mut(buffers, &self.handle)?.set_data(data);
+        Ok(())
+    }
+
+    /// Overwrites the elements starting at `offset` of a buffer that holds only an array
+    pub fn write_range<DataTy: ShaderType + ShaderSize + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        offset: usize,
+        data: &[DataTy],
+    ) -> Result<(), WriteError> {
+        self.write_field_range(buffers, 0, offset, data)
+    }
+
+    /// Overwrites the elements starting at `offset` of an array that starts `array_offset` bytes into the buffer,
+    /// like the runtime sized array at the end of a struct
+    pub fn write_field_range<DataTy: ShaderType + ShaderSize + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        array_offset: usize,
+        offset: usize,
+        data: &[DataTy],
+    ) -> Result<(), WriteError> {
+        let buffer = asset_mut(buffers, &self.handle)?;
+        write_storage_range(buffer, array_offset, offset, data)
+    }
+}
+impl ReadWriteBuffer<Image> {
+    /// Replaces every texel of the image, `data` must cover the whole image
+    /// with one element per texel of the image format, like `[u8; 4]` for `Rgba8Unorm`
+    pub fn set<TexelTy: Pod>(
+        &self,
+        images: &mut Assets<Image>,
+        data: &[TexelTy],
+    ) -> Result<(), WriteError> {
+        set_texels(asset_mut(images, &self.handle)?, data)
+    }
+
+    /// Overwrites the texels starting at `offset`, counted in row order
+    pub fn write_range<TexelTy: Pod>(
+        &self,
+        images: &mut Assets<Image>,
+        offset: usize,
+        data: &[TexelTy],
+    ) -> Result<(), WriteError> {
+        write_texels(asset_mut(images, &self.handle)?, offset, data)
+    }
+}
+impl WriteableBuffer for ReadWriteBuffer<ShaderStorageBuffer> {
+    type T = ShaderStorageBuffer;
+
+    fn get_mut<'a>(&'a self, buffers: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
+    where
+        Self::T: Asset,
+    {
+        buffers
+            .get_mut(&self.handle)
+            .expect("Missing Read Write Buffer")
+    }
+}
+impl WriteableBuffer for ReadWriteBuffer<Image> {
+    type T = Image;
+
+    fn get_mut<'a>(&'a self, images: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
+    where
+        Self::T: Asset,
+    {
+        images
+            .get_mut(&self.handle)
+            .expect("Missing Read Write Image")
+    }
+}
+
+// Uniforms are always read only on the GPU side, and are updated from the CPU
+pub struct UniformBuffer<T: Asset> {
+    pub handle: Handle<T>,
+}
+impl<T: Asset> Clone for UniformBuffer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+        }
+    }
+}
+impl<T: Asset> From<Handle<T>> for UniformBuffer<T> {
+    fn from(data: Handle<T>) -> Self {
+        Self { handle: data }
+    }
+}
+impl UniformBuffer<ShaderStorageBuffer> {
+    /// Replaces the uniform value, the GPU sees it once the buffer asset is re-uploaded
+    pub fn set<DataTy: ShaderType + WriteInto>(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        data: DataTy,
+    ) -> Result<(), WriteError> {
+        asset_mut(buffers, &self.handle)?.data = Some(uniform_bytes(data));
+        Ok(())
+    }
+}
+impl WriteableBuffer for UniformBuffer<ShaderStorageBuffer> {
+    type T = ShaderStorageBuffer;
+
+    fn get_mut<'a>(&'a self, buffers: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
+    where
+        Self::T: Asset,
+    {
+        buffers
+            .get_mut(&self.handle)
+            .expect("Missing Uniform Buffer")
+    }
+}
+
+/// Which half of every [`PingPong`] of a group was written last
+///
+/// The compute node swaps the halves in the render world, the main world copy is updated during
+/// extraction so it lags a frame behind
+#[derive(Resource, Debug)]
+pub struct PingPongFront<BuffersTy> {
+    front: usize,
+    _phantom: PhantomData<fn() -> BuffersTy>,
+}
+impl<T> Clone for PingPongFront<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for PingPongFront<T> {}
+impl<T> Default for PingPongFront<T> {
+    fn default() -> Self {
+        Self {
+            front: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<T> PingPongFront<T> {
+    pub fn front(&self) -> usize {
+        self.front
+    }
+
+    /// The most recently written half of `ping_pong`
+    pub fn get<'a, A: Asset>(&self, ping_pong: &'a PingPong<A>) -> &'a Handle<A> {
+        &ping_pong.halves[self.front]
+    }
+
+    pub(super) fn set(&mut self, front: usize) {
+        self.front = front;
+    }
+}
+
+// Two copies of the same binding, the shader reads one half and writes the other, and the halves
+// swap after every round of dispatches, see [`PingPongFront`]
+pub struct PingPong<T: Asset> {
+    pub halves: [Handle<T>; 2],
+}
+impl<T: Asset> Clone for PingPong<T> {
+    fn clone(&self) -> Self {
+        Self {
+            halves: self.halves.clone(),
+        }
+    }
+}
+impl<T: Asset> PingPong<T> {
+    pub fn new(front: Handle<T>, back: Handle<T>) -> Self {
+        Self {
+            halves: [front, back],
+        }
+    }
+
+    fn half(&self, swapped: bool, output: bool) -> &Handle<T> {
+        &self.halves[(swapped ^ output) as usize]
+    }
+}
+impl PingPong<ShaderStorageBuffer> {
+    /// A readback which follows the most recently written half of the `BuffersTy` group
+    pub fn readback<BuffersTy>(&self) -> (Readback, PingPongReadback<BuffersTy>) {
+        PingPongReadback::new(self.halves.clone().map(Readback::Buffer))
+    }
+}
+impl PingPong<Image> {
+    /// A readback which follows the most recently written half of the `BuffersTy` group
+    pub fn readback<BuffersTy>(&self) -> (Readback, PingPongReadback<BuffersTy>) {
+        PingPongReadback::new(self.halves.clone().map(Readback::Texture))
+    }
+}
+
+/// Moves the extracted [`Readback`] of the same entity to the most recently written half, which
+/// the render world knows before the readback is copied
+#[derive(Component)]
+pub struct PingPongReadback<BuffersTy> {
+    halves: [Readback; 2],
+    _phantom: PhantomData<fn() -> BuffersTy>,
+}
+impl<T> Clone for PingPongReadback<T> {
+    fn clone(&self) -> Self {
+        Self {
+            halves: self.halves.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<T> PingPongReadback<T> {
+    fn new(halves: [Readback; 2]) -> (Readback, Self) {
+        (
+            halves[0].clone(),
+            Self {
+                halves,
+                _phantom: PhantomData,
+            },
+        )
+    }
+}
+impl<T: Send + Sync + 'static> ExtractComponent for PingPongReadback<T> {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// The group and binding a readback reads, and the retargeting of ping pong readbacks
+pub type BindingReadback<BuffersTy> = ((u32, u32), Readback, Option<PingPongReadback<BuffersTy>>);
+
+pub(super) fn retarget_readbacks<BuffersTy: Send + Sync + 'static>(
+    front: Res<PingPongFront<BuffersTy>>,
+    mut readbacks: Query<(&mut Readback, &PingPongReadback<BuffersTy>)>,
+) {
+    for (mut readback, ping_pong) in readbacks.iter_mut() {
+        *readback = ping_pong.halves[front.front()].clone();
+    }
+}
+
+pub(super) fn extract_ping_pong_front<BuffersTy: Send + Sync + 'static>(
+    mut main_world: ResMut<MainWorld>,
+    front: Res<PingPongFront<BuffersTy>>,
+) {
+    main_world.insert_resource(*front);
+}
 
 // The traits are CPUT land Read/Write terms so:
 // Readable  -> GPU Wrote some data that I want to read via readback
@@ -154,6 +692,7 @@ pub trait ReadableBuffer {
 }
 pub trait WriteableBuffer {
     type T;
+    /// Modifying the asset re-uploads it, which the next dispatch picks up
     fn get_mut<'a>(&'a self, buffers: &'a mut ResMut<Assets<Self::T>>) -> &'a mut Self::T
     where
         Self::T: Asset;
@@ -162,6 +701,119 @@ pub trait WriteableBuffer {
 pub trait HandleIntoBinding {
     type T;
     fn binding<'b>(&self, assets: &'b Self::T) -> BindingResource<'b>;
+    /// The GPU resource currently bound, `None` while the asset is not prepared yet
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId>;
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent>;
+    fn asset(&self) -> BindingAsset;
+}
+
+/// Identifies the GPU resource behind a binding, which changes whenever an asset is re-uploaded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceId {
+    Buffer(BufferId),
+    TextureView(TextureViewId),
+}
+
+fn storage_id(
+    assets: &RenderAssets<GpuShaderStorageBuffer>,
+    handle: &Handle<ShaderStorageBuffer>,
+) -> Option<ResourceId> {
+    assets
+        .get(handle)
+        .map(|buffer| ResourceId::Buffer(buffer.buffer.id()))
+}
+
+fn image_id(assets: &RenderAssets<GpuImage>, handle: &Handle<Image>) -> Option<ResourceId> {
+    assets
+        .get(handle)
+        .map(|image| ResourceId::TextureView(image.texture_view.id()))
+}
+
+pub fn gpu_buffer(
+    assets: &RenderAssets<GpuShaderStorageBuffer>,
+    handle: &Handle<ShaderStorageBuffer>,
+) -> Option<Buffer> {
+    assets.get(handle).map(|buffer| buffer.buffer.clone())
+}
+
+/// How much data a binding currently holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingExtent {
+    /// Length of the GPU buffer in bytes
+    Buffer(u64),
+    /// Width, height and depth of the texture in texels
+    Texture(UVec3),
+}
+
+fn storage_extent(
+    assets: &RenderAssets<GpuShaderStorageBuffer>,
+    handle: &Handle<ShaderStorageBuffer>,
+) -> Option<BindingExtent> {
+    assets
+        .get(handle)
+        .map(|buffer| BindingExtent::Buffer(buffer.buffer.size()))
+}
+
+fn image_extent(assets: &RenderAssets<GpuImage>, handle: &Handle<Image>) -> Option<BindingExtent> {
+    assets.get(handle).map(|image| {
+        let depth = image.texture.depth_or_array_layers();
+        BindingExtent::Texture(image.size.extend(depth))
+    })
+}
+
+/// The main world asset behind a binding
+#[derive(Clone, Debug)]
+pub enum BindingAsset {
+    Buffer(Handle<ShaderStorageBuffer>),
+    Texture(Handle<Image>),
+}
+
+pub trait PingPongBinding {
+    type T;
+    /// `output` selects the half the shader writes
+    fn binding<'b>(&self, assets: &'b Self::T, swapped: bool, output: bool) -> BindingResource<'b>;
+    fn resource_id(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<ResourceId>;
+    fn extent(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<BindingExtent>;
+    fn asset(&self, swapped: bool, output: bool) -> BindingAsset;
+}
+
+impl PingPongBinding for PingPong<ShaderStorageBuffer> {
+    type T = RenderAssets<GpuShaderStorageBuffer>;
+    fn binding<'b>(&self, assets: &'b Self::T, swapped: bool, output: bool) -> BindingResource<'b> {
+        assets
+            .get(self.half(swapped, output))
+            .expect("Missing GPU Storage Buffer")
+            .buffer
+            .as_entire_binding()
+    }
+    fn resource_id(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<ResourceId> {
+        storage_id(assets, self.half(swapped, output))
+    }
+    fn extent(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<BindingExtent> {
+        storage_extent(assets, self.half(swapped, output))
+    }
+    fn asset(&self, swapped: bool, output: bool) -> BindingAsset {
+        BindingAsset::Buffer(self.half(swapped, output).clone())
+    }
+}
+impl PingPongBinding for PingPong<Image> {
+    type T = RenderAssets<GpuImage>;
+    fn binding<'b>(&self, assets: &'b Self::T, swapped: bool, output: bool) -> BindingResource<'b> {
+        assets
+            .get(self.half(swapped, output))
+            .expect("Missing GPU Image")
+            .texture_view
+            .into_binding()
+    }
+    fn resource_id(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<ResourceId> {
+        image_id(assets, self.half(swapped, output))
+    }
+    fn extent(&self, assets: &Self::T, swapped: bool, output: bool) -> Option<BindingExtent> {
+        image_extent(assets, self.half(swapped, output))
+    }
+    fn asset(&self, swapped: bool, output: bool) -> BindingAsset {
+        BindingAsset::Texture(self.half(swapped, output).clone())
+    }
 }
 
 // Storage Buffers
@@ -174,6 +826,15 @@ impl HandleIntoBinding for ReadBuffer<ShaderStorageBuffer> {
             .buffer
             .as_entire_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        storage_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        storage_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Buffer(self.handle.clone())
+    }
 }
 impl HandleIntoBinding for WriteBuffer<ShaderStorageBuffer> {
     type T = RenderAssets<GpuShaderStorageBuffer>;
@@ -184,6 +845,15 @@ impl HandleIntoBinding for WriteBuffer<ShaderStorageBuffer> {
             .buffer
             .as_entire_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        storage_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        storage_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Buffer(self.handle.clone())
+    }
 }
 
 impl HandleIntoBinding for ReadWriteBuffer<ShaderStorageBuffer> {
@@ -195,6 +865,34 @@ impl HandleIntoBinding for ReadWriteBuffer<ShaderStorageBuffer> {
             .buffer
             .as_entire_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        storage_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        storage_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Buffer(self.handle.clone())
+    }
+}
+impl HandleIntoBinding for UniformBuffer<ShaderStorageBuffer> {
+    type T = RenderAssets<GpuShaderStorageBuffer>;
+    fn binding<'b>(&self, assets: &'b Self::T) -> BindingResource<'b> {
+        assets
+            .get(&self.handle)
+            .expect("Missing GPU Uniform Buffer")
+            .buffer
+            .as_entire_binding()
+    }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        storage_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        storage_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Buffer(self.handle.clone())
+    }
 }
 // Texture Buffers
 impl HandleIntoBinding for ReadBuffer<Image> {
@@ -206,6 +904,15 @@ impl HandleIntoBinding for ReadBuffer<Image> {
             .texture_view
             .into_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        image_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        image_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Texture(self.handle.clone())
+    }
 }
 impl HandleIntoBinding for WriteBuffer<Image> {
     type T = RenderAssets<GpuImage>;
@@ -216,6 +923,15 @@ impl HandleIntoBinding for WriteBuffer<Image> {
             .texture_view
             .into_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        image_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        image_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Texture(self.handle.clone())
+    }
 }
 
 impl HandleIntoBinding for ReadWriteBuffer<Image> {
@@ -227,4 +943,13 @@ impl HandleIntoBinding for ReadWriteBuffer<Image> {
             .texture_view
             .into_binding()
     }
+    fn resource_id(&self, assets: &Self::T) -> Option<ResourceId> {
+        image_id(assets, &self.handle)
+    }
+    fn extent(&self, assets: &Self::T) -> Option<BindingExtent> {
+        image_extent(assets, &self.handle)
+    }
+    fn asset(&self) -> BindingAsset {
+        BindingAsset::Texture(self.handle.clone())
+    }
 }
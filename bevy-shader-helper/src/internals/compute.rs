@@ -1,16 +1,27 @@
 use std::marker::PhantomData;
 
-use bevy_ecs::{system::Resource, world::World};
+use bevy_ecs::{
+    system::Resource,
+    world::{Mut, World},
+};
 use bevy_render::{
     render_graph::{self, NodeRunError, RenderGraphContext},
-    render_resource::{ComputePassDescriptor, PipelineCache},
+    render_resource::PipelineCache,
     renderer::RenderContext,
 };
+use bevy_time::Time;
 
 use super::{
     binding::GenericBindGroup,
-    entries::{Dispatch, ShaderEntry},
+    buffers::PingPongFront,
+    control::{ControlState, ShaderControl},
+    entries::{
+        CompiledPipelines, Dispatch, DispatchContext, PassRecorder, RequestedDispatches,
+        ShaderEntry,
+    },
     pipeline::Pipeline,
+    status::{ShaderReport, ShaderStatus},
+    workgroups::WorkgroupSources,
 };
 
 #[derive(Default)]
@@ -18,45 +29,193 @@ pub(super) enum ShaderStage {
     #[default]
     Loading,
     Startup,
-    Update, // TODO: somehow allow for end user to do fancy state things such as the gol example of buffer swapping
+    Update,
+}
+
+/// How the shader behaves while the pipeline cache recompiles it after the shader asset changed
+///
+/// Shaders loaded from a path only change with bevy's `file_watcher` feature enabled
+#[derive(Clone, Copy, Debug)]
+pub struct HotReload {
+    /// Dispatch the startup entries again once the changed shader compiled
+    pub rerun_startup: bool,
+    /// Keep dispatching the previous pipelines while recompiling, and when the changed shader
+    /// fails to compile, instead of pausing every dispatch
+    pub keep_previous: bool,
 }
 
-pub(super) struct ComputeNode<PipelineTy, EntryTy> {
+impl Default for HotReload {
+    fn default() -> Self {
+        Self {
+            rerun_startup: false,
+            keep_previous: true,
+        }
+    }
+}
+
+/// Dispatches the entries of a plugin, `BuffersTy` keys its [`PingPongFront`]
+pub(super) struct ComputeNode<PipelineTy, EntryTy, BuffersTy> {
     state: ShaderStage,
     dispatches: Dispatch<EntryTy>,
-    _phantom: PhantomData<PipelineTy>,
+    pipelines: CompiledPipelines,
+    /// `pipelines` without the entries disabled through [`ShaderControl`]
+    enabled: CompiledPipelines,
+    control: ControlState,
+    hot_reload: HotReload,
+    /// Times every dispatched entry through the render diagnostics
+    timings: bool,
+    /// Set once a compiled pipeline was invalidated, until every entry compiled again
+    reloading: bool,
+    /// Set while nothing should be dispatched, because of errors or a reload without previous pipelines
+    paused: bool,
+    frame: u64,
+    /// Virtual time not yet dispatched by every fixed rate update entry
+    accumulated: Vec<f64>,
+    /// Dispatches of every update entry this frame
+    counts: Vec<u32>,
+    /// Rounds of dispatches this frame, planned in `update` since `run` cannot change the node
+    rounds: u32,
+    /// The ping pong half the first round reads
+    front: usize,
+    _phantom: PhantomData<(PipelineTy, BuffersTy)>,
 }
 
-impl<PipelineTy, EntryTy> ComputeNode<PipelineTy, EntryTy> {
-    pub(super) fn new(dispatches: Dispatch<EntryTy>) -> Self {
+impl<PipelineTy, EntryTy, BuffersTy> ComputeNode<PipelineTy, EntryTy, BuffersTy> {
+    pub(super) fn new(dispatches: Dispatch<EntryTy>, hot_reload: HotReload, timings: bool) -> Self {
         Self {
             state: ShaderStage::Loading,
             dispatches,
+            pipelines: CompiledPipelines::default(),
+            enabled: CompiledPipelines::default(),
+            control: ControlState::default(),
+            hot_reload,
+            timings,
+            reloading: false,
+            paused: false,
+            frame: 0,
+            accumulated: vec![],
+            counts: vec![],
+            rounds: 0,
+            front: 0,
             _phantom: Default::default(),
         }
     }
 }
 
-impl<PipelineTy: Resource + Pipeline, EntryTy: ShaderEntry + Send + Sync + 'static>
-    render_graph::Node for ComputeNode<PipelineTy, EntryTy>
+impl<
+    PipelineTy: Resource + Pipeline,
+    EntryTy: ShaderEntry + Clone + PartialEq + Send + Sync + 'static,
+    BuffersTy: Send + Sync + 'static,
+> render_graph::Node for ComputeNode<PipelineTy, EntryTy, BuffersTy>
 {
     fn update(&mut self, world: &mut World) {
+        // Every round of the previous frame swapped the halves
+        self.front ^= (self.rounds % 2) as usize;
+        self.rounds = 0;
+
         let pipeline_cache = world.resource::<PipelineCache>();
         let pipeline = world.resource::<PipelineTy>();
+        let requested = &world.resource::<RequestedDispatches<EntryTy>>().0;
+        let sources = WorkgroupSources::<PipelineTy, EntryTy>::new(world);
 
+        let errors = self.dispatches.errors(requested, pipeline_cache, pipeline);
+        let compiled = self.pipelines.refresh(
+            self.dispatches.entries(requested),
+            pipeline_cache,
+            pipeline,
+            self.hot_reload.keep_previous,
+        );
+
+        let control = world.get_resource::<ShaderControl<EntryTy>>();
+        let (frozen, reset) = self.control.advance(control);
+        self.enabled = self.pipelines.without(&self.control.disabled);
+
+        // Startup entries only count as dispatched when the previous frame was not paused
+        let ran = !self.paused;
         match self.state {
-            ShaderStage::Loading => {
-                if self.dispatches.on_startup_success(pipeline_cache, pipeline) {
-                    self.state = ShaderStage::Startup
-                }
+            // Startup entries dispatched before the bind group exists would be skipped
+            ShaderStage::Loading
+                if self.dispatches.on_startup_success(pipeline_cache, pipeline)
+                    && self.dispatches.on_startup_resolved(&sources)
+                    && world.contains_resource::<GenericBindGroup<PipelineTy>>() =>
+            {
+                self.state = ShaderStage::Startup
             }
-            ShaderStage::Startup => {
-                if self.dispatches.on_update_success(pipeline_cache, pipeline) {
-                    self.state = ShaderStage::Update
-                }
+            ShaderStage::Startup
+                if ran && self.dispatches.on_update_success(pipeline_cache, pipeline) =>
+            {
+                self.state = ShaderStage::Update
             }
             _ => {}
         }
+
+        // Past loading every entry compiled before, so a missing pipeline means the shader changed
+        let loading = matches!(self.state, ShaderStage::Loading);
+        if !compiled && !loading {
+            self.reloading = true;
+        } else if compiled && self.reloading {
+            self.reloading = false;
+            if self.hot_reload.rerun_startup {
+                self.state = ShaderStage::Startup;
+            }
+        }
+        if reset && !loading {
+            self.state = ShaderStage::Startup;
+            self.frame = 0;
+            self.accumulated.clear();
+        }
+        self.paused =
+            frozen || !self.hot_reload.keep_previous && (self.reloading || !errors.is_empty());
+
+        let status = if !errors.is_empty() {
+            ShaderStatus::Failed(errors)
+        } else if loading {
+            ShaderStatus::Loading
+        } else if self.reloading {
+            ShaderStatus::Reloading
+        } else {
+            ShaderStatus::Ready
+        };
+        world.resource_mut::<ShaderReport<EntryTy>>().update(status);
+
+        self.counts = match self.state {
+            ShaderStage::Update if !self.paused => {
+                let delta = world.resource::<Time>().delta();
+                self.frame += 1;
+                self.dispatches
+                    .update_counts(self.frame, delta, &mut self.accumulated)
+            }
+            _ => vec![],
+        };
+
+        // Failed requests were reported above, and would keep the shader paused if they stayed
+        world.resource_scope(|world, mut requested: Mut<RequestedDispatches<EntryTy>>| {
+            let pipeline_cache = world.resource::<PipelineCache>();
+            let pipeline = world.resource::<PipelineTy>();
+            Dispatch::discard_failed_requests(&mut requested.0, pipeline_cache, pipeline);
+        });
+
+        self.dispatches.on_request.clear();
+        if loading || self.paused {
+            return;
+        }
+        let requested = &mut world.resource_mut::<RequestedDispatches<EntryTy>>().0;
+        self.dispatches
+            .take_ready_requests(requested, &self.pipelines, &self.control.disabled);
+
+        // Not created until every bound asset has been uploaded
+        if !world.contains_resource::<GenericBindGroup<PipelineTy>>() {
+            return;
+        }
+        let startup = matches!(self.state, ShaderStage::Startup);
+        let sources = WorkgroupSources::<PipelineTy, EntryTy>::new(world);
+        self.rounds = self
+            .dispatches
+            .rounds(startup, &self.counts, &self.enabled, &sources);
+        // Readbacks of the next frame are retargeted to it before this node updates again
+        if let Some(mut front) = world.get_resource_mut::<PingPongFront<BuffersTy>>() {
+            front.set(self.front ^ (self.rounds % 2) as usize);
+        }
     }
 
     fn run(
@@ -65,30 +224,36 @@ impl<PipelineTy: Resource + Pipeline, EntryTy: ShaderEntry + Send + Sync + 'stat
         render_context: &mut RenderContext,
         world: &World,
     ) -> Result<(), NodeRunError> {
-        let pipeline_cache = world.resource::<PipelineCache>();
-        let pipeline = world.resource::<PipelineTy>();
-        let bind_group = world.resource::<GenericBindGroup<PipelineTy>>();
-        let mut pass =
-            render_context
-                .command_encoder()
-                .begin_compute_pass(&ComputePassDescriptor {
-                    label: PipelineTy::label(),
-                    ..Default::default()
-                });
-        match self.state {
-            ShaderStage::Startup => {
-                self.dispatches.on_startup_dispatch(
-                    pipeline_cache,
-                    pipeline,
-                    &mut pass,
-                    bind_group,
-                );
-            }
-            ShaderStage::Update => {
-                self.dispatches
-                    .on_update_dispatch(pipeline_cache, pipeline, &mut pass, bind_group);
+        if self.rounds == 0 {
+            return Ok(());
+        }
+        let Some(bind_group) = world.get_resource::<GenericBindGroup<PipelineTy>>() else {
+            return Ok(());
+        };
+        let diagnostics = self.timings.then(|| render_context.diagnostic_recorder());
+        let mut recorder = PassRecorder::new(
+            render_context.command_encoder(),
+            PipelineTy::label(),
+            diagnostics,
+        );
+        let mut context = DispatchContext {
+            pipeline: world.resource::<PipelineTy>(),
+            pipelines: &self.enabled,
+            bind_group,
+            front: self.front,
+            workgroups: WorkgroupSources::new(world),
+        };
+        // Every round dispatches each entry at most once, and swaps the ping pong halves afterwards
+        for round in 0..self.rounds {
+            context.front = self.front ^ (round % 2) as usize;
+            if round == 0 {
+                if matches!(self.state, ShaderStage::Startup) {
+                    self.dispatches.on_startup_dispatch(&context, &mut recorder);
+                }
+                self.dispatches.on_request_dispatch(&context, &mut recorder);
             }
-            _ => {}
+            self.dispatches
+                .on_update_dispatch(&context, &self.counts, round, &mut recorder);
         }
 
         Ok(())
@@ -0,0 +1,128 @@
+use std::{collections::HashSet, marker::PhantomData};
+
+use bevy_ecs::system::Resource;
+use bevy_render::extract_resource::ExtractResource;
+
+use super::entries::ShaderEntry;
+
+/// Pauses, steps and resets the dispatches of a shader plugin
+/// ```ignore
+/// fn debug_controls(keys: Res<ButtonInput<KeyCode>>, mut control: ResMut<ShaderControl<HelloEntries>>) {
+///     if keys.just_pressed(KeyCode::Space) {
+///         control.pause();
+///     }
+///     if keys.just_pressed(KeyCode::ArrowRight) {
+///         control.step(1);
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct ShaderControl<EntryTy> {
+    paused: bool,
+    /// Every step ever requested, the render world tracks how many it already ran
+    steps: u64,
+    /// Every reset ever requested
+    resets: u64,
+    /// Keys of the disabled entries
+    disabled: HashSet<usize>,
+    _phantom: PhantomData<fn() -> EntryTy>,
+}
+
+impl<T> Clone for ShaderControl<T> {
+    fn clone(&self) -> Self {
+        Self {
+            paused: self.paused,
+            steps: self.steps,
+            resets: self.resets,
+            disabled: self.disabled.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for ShaderControl<T> {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            steps: 0,
+            resets: 0,
+            disabled: HashSet::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EntryTy: ShaderEntry> ShaderControl<EntryTy> {
+    /// Stops every dispatch, requested dispatches wait until the shader resumes
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Runs the dispatches of `n` more frames while paused, one per frame
+    pub fn step(&mut self, n: u32) {
+        if self.paused {
+            self.steps += n as u64;
+        }
+    }
+
+    /// Dispatches the startup entries again, before going back to the update entries
+    pub fn reset(&mut self) {
+        self.resets += 1;
+    }
+
+    pub fn enable(&mut self, entry: &EntryTy) {
+        self.disabled.remove(&entry.as_key());
+    }
+
+    /// Skips the entry in every dispatch, its requested dispatches wait until it is enabled again
+    pub fn disable(&mut self, entry: &EntryTy) {
+        self.disabled.insert(entry.as_key());
+    }
+
+    pub fn is_enabled(&self, entry: &EntryTy) -> bool {
+        !self.disabled.contains(&entry.as_key())
+    }
+}
+
+impl<T: Send + Sync + 'static> ExtractResource for ShaderControl<T> {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+/// What the compute node already consumed of its [`ShaderControl`]
+#[derive(Default)]
+pub(super) struct ControlState {
+    steps: u64,
+    resets: u64,
+    pub disabled: HashSet<usize>,
+}
+
+impl ControlState {
+    /// Returns whether the node is frozen this frame, and whether a reset was requested
+    pub(super) fn advance<T>(&mut self, control: Option<&ShaderControl<T>>) -> (bool, bool) {
+        let Some(control) = control else {
+            return (false, false);
+        };
+        self.disabled.clone_from(&control.disabled);
+        let reset = self.resets != control.resets;
+        self.resets = control.resets;
+        if !control.paused || self.steps >= control.steps {
+            self.steps = control.steps;
+            return (control.paused, reset);
+        }
+        self.steps += 1;
+
+        (false, reset)
+    }
+}
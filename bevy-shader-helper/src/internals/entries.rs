@@ -1,31 +1,84 @@
-use crate::internals::{binding::GenericBindGroup, pipeline::Pipeline};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
-use bevy_render::render_resource::{CachedPipelineState, ComputePass, PipelineCache};
+use crate::internals::{
+    binding::GenericBindGroup,
+    pipeline::Pipeline,
+    schedule::{DispatchSchedule, RunCondition},
+    status::ShaderError,
+    workgroups::{ResolvedWorkgroups, WorkgroupSources, Workgroups},
+};
+use tracing::warn;
+
+use bevy_ecs::{
+    event::{Event, EventReader},
+    schedule::Condition,
+    system::{ResMut, Resource},
+};
+use bevy_render::{
+    Extract,
+    diagnostic::RecordDiagnostics,
+    render_resource::{
+        Buffer, CachedPipelineState, CommandEncoder, ComputePass, ComputePassDescriptor,
+        ComputePipeline, PipelineCache, PipelineCacheError,
+    },
+};
 
 pub use bevy_shader_macros::ShaderEntry;
 pub trait ShaderEntry {
     fn as_key(&self) -> usize;
+    /// The variant name, which names the debug group and timings of the entry
+    fn name(&self) -> &'static str;
 }
 
 #[derive(Clone, Debug)]
 pub struct Entry<EntryTy> {
     pub entry: EntryTy,
-    pub workgroup: (u32, u32, u32),
+    pub workgroup: Workgroups,
+    /// Only used by update entries, startup and requested entries dispatch once
+    pub schedule: DispatchSchedule,
+    /// Update entries are skipped while the condition does not hold
+    pub run_if: Option<RunCondition>,
 }
-impl<T> From<(T, u32, u32, u32)> for Entry<T> {
-    fn from(value: (T, u32, u32, u32)) -> Self {
+impl<T> Entry<T> {
+    pub fn new(entry: T, workgroup: impl Into<Workgroups>) -> Self {
         Self {
-            entry: value.0,
-            workgroup: (value.1, value.2, value.3),
+            entry,
+            workgroup: workgroup.into(),
+            schedule: DispatchSchedule::EveryFrame,
+            run_if: None,
         }
     }
+
+    /// ```ignore
+    /// Entry::new(HelloEntries::Update, (2, 1, 1)).schedule(DispatchSchedule::FixedRate {
+    ///     hz: 60.,
+    ///     max_substeps: 4,
+    /// })
+    /// ```
+    pub fn schedule(mut self, schedule: DispatchSchedule) -> Self {
+        self.schedule = schedule;
+
+        self
+    }
+
+    /// Accepts any main world condition, such as `in_state(GameState::Running)`
+    pub fn run_if<M>(mut self, condition: impl Condition<M>) -> Self {
+        self.run_if = Some(RunCondition::new(condition));
+
+        self
+    }
 }
-impl<T, V: Into<(u32, u32, u32)>> From<(T, V)> for Entry<T> {
+impl<T> From<(T, u32, u32, u32)> for Entry<T> {
+    fn from(value: (T, u32, u32, u32)) -> Self {
+        Self::new(value.0, (value.1, value.2, value.3))
+    }
+}
+impl<T, V: Into<Workgroups>> From<(T, V)> for Entry<T> {
     fn from(value: (T, V)) -> Self {
-        Self {
-            entry: value.0,
-            workgroup: value.1.into(),
-        }
+        Self::new(value.0, value.1)
     }
 }
 
@@ -40,26 +93,176 @@ impl<EntryTy: ShaderEntry> Entry<EntryTy> {
         pipeline_cache.get_compute_pipeline_state(pipeline.get_id(&self.entry))
     }
 
+    /// Whether the entry has a pipeline and workgroup count to dispatch
+    fn dispatchable<PipelineTy>(
+        &self,
+        pipelines: &CompiledPipelines,
+        sources: &WorkgroupSources<PipelineTy, EntryTy>,
+    ) -> bool {
+        let key = self.entry.as_key();
+        pipelines.get(&self.entry).is_some() && self.workgroup.resolve(key, sources).is_some()
+    }
+
+    /// Skipped when the entry is not [`dispatchable`](Self::dispatchable)
     fn dispatch<PipelineTy: Pipeline>(
         &self,
-        pipeline_cache: &PipelineCache,
-        pipeline: &PipelineTy,
-        pass: &mut ComputePass,
-        bind_group: &GenericBindGroup<PipelineTy>,
+        context: &DispatchContext<PipelineTy, EntryTy>,
+        recorder: &mut PassRecorder<impl RecordDiagnostics>,
     ) {
-        if let Some(pipeline) = pipeline_cache.get_compute_pipeline(pipeline.get_id(&self.entry)) {
-            pass.set_bind_group(0, &bind_group.0, &[]);
+        let Some(pipeline) = context.pipelines.get(&self.entry) else {
+            return;
+        };
+        let key = self.entry.as_key();
+        let Some(workgroups) = self.workgroup.resolve(key, &context.workgroups) else {
+            return;
+        };
+        if let ResolvedWorkgroups::Indirect {
+            bound, arguments, ..
+        } = workgroups
+        {
+            recorder.copy(bound, arguments);
+        }
+        let label = context.pipeline.entry_label(&self.entry);
+        recorder.record(self.entry.name(), label, |pass| {
+            for (group, bind_group) in context.bind_group.current(context.front).iter().enumerate()
+            {
+                pass.set_bind_group(group as u32, bind_group, &[]);
+            }
             pass.set_pipeline(pipeline);
-            pass.dispatch_workgroups(self.workgroup.0, self.workgroup.1, self.workgroup.2);
+            match workgroups {
+                ResolvedWorkgroups::Direct(x, y, z) => pass.dispatch_workgroups(x, y, z),
+                ResolvedWorkgroups::Indirect {
+                    arguments, offset, ..
+                } => pass.dispatch_workgroups_indirect(arguments, offset),
+            }
+        });
+    }
+}
+
+/// Everything the entries of a frame are dispatched with
+pub(super) struct DispatchContext<'a, PipelineTy, EntryTy> {
+    pub pipeline: &'a PipelineTy,
+    pub pipelines: &'a CompiledPipelines,
+    pub bind_group: &'a GenericBindGroup<PipelineTy>,
+    /// The ping pong half read by the current round
+    pub front: usize,
+    pub workgroups: WorkgroupSources<'a, PipelineTy, EntryTy>,
+}
+
+/// Records the dispatches of a frame, buffers can only be copied in between compute passes
+pub(super) struct PassRecorder<'a, D> {
+    encoder: &'a mut CommandEncoder,
+    label: Option<&'static str>,
+    pass: Option<ComputePass<'static>>,
+    /// Set when timing entries
+    diagnostics: Option<D>,
+}
+
+impl<'a, D: RecordDiagnostics> PassRecorder<'a, D> {
+    pub(super) fn new(
+        encoder: &'a mut CommandEncoder,
+        label: Option<&'static str>,
+        diagnostics: Option<D>,
+    ) -> Self {
+        Self {
+            encoder,
+            label,
+            pass: None,
+            diagnostics,
         }
     }
+
+    /// Records the commands of an entry inside a debug group named after its label, or its name
+    ///
+    /// Timed entries are spanned inside the shared pass, so timing does not change how the
+    /// dispatches are batched
+    fn record(
+        &mut self,
+        name: &'static str,
+        label: Option<&'static str>,
+        commands: impl FnOnce(&mut ComputePass<'static>),
+    ) {
+        let encoder = &mut *self.encoder;
+        let pass_label = self.label;
+        let pass = self.pass.get_or_insert_with(|| {
+            encoder
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: pass_label,
+                    ..Default::default()
+                })
+                .forget_lifetime()
+        });
+        pass.push_debug_group(label.unwrap_or(name));
+        let span = self
+            .diagnostics
+            .as_ref()
+            .map(|diagnostics| diagnostics.pass_span(pass, name));
+        commands(pass);
+        if let Some(span) = span {
+            span.end(pass);
+        }
+        pass.pop_debug_group();
+    }
+
+    /// Ends the current pass, the next dispatch begins a new one
+    fn copy(&mut self, source: &Buffer, destination: &Buffer) {
+        self.pass = None;
+        self.encoder
+            .copy_buffer_to_buffer(source, 0, destination, 0, source.size());
+    }
+}
+
+/// The last compiled pipeline of every entry, which outlives the pipeline cache recompiling a changed shader
+#[derive(Default)]
+pub(super) struct CompiledPipelines(HashMap<usize, ComputePipeline>);
+
+impl CompiledPipelines {
+    /// Whether every entry is compiled from the current shader, `keep_previous` keeps the pipelines
+    /// of entries which are not
+    pub(super) fn refresh<'a, EntryTy: ShaderEntry + 'a, PipelineTy: Pipeline>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'a Entry<EntryTy>>,
+        pipeline_cache: &PipelineCache,
+        pipeline: &PipelineTy,
+        keep_previous: bool,
+    ) -> bool {
+        let mut compiled = true;
+        for entry in entries {
+            let key = entry.entry.as_key();
+            match pipeline_cache.get_compute_pipeline(pipeline.get_id(&entry.entry)) {
+                Some(pipeline) => {
+                    self.0.insert(key, pipeline.clone());
+                }
+                None => {
+                    compiled = false;
+                    if !keep_previous {
+                        self.0.remove(&key);
+                    }
+                }
+            }
+        }
+
+        compiled
+    }
+
+    fn get<EntryTy: ShaderEntry>(&self, entry: &EntryTy) -> Option<&ComputePipeline> {
+        self.0.get(&entry.as_key())
+    }
+
+    /// The pipelines of every entry not in `disabled`, keyed by [`ShaderEntry::as_key`]
+    pub(super) fn without(&self, disabled: &HashSet<usize>) -> Self {
+        let pipelines = self.0.iter().filter(|(key, _)| !disabled.contains(key));
+        Self(pipelines.map(|(key, p)| (*key, p.clone())).collect())
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct Dispatch<EntryTy> {
     pub on_startup: Vec<Entry<EntryTy>>,
     pub on_update: Vec<Entry<EntryTy>>,
-    // TODO: on_request: Vec<(receiver, ShaderDispatch)>
+    /// Requested through [`DispatchShader`] events and ready to dispatch, only ever filled for the
+    /// current frame
+    pub on_request: Vec<Entry<EntryTy>>,
 }
 
 impl<T, E1: Into<Vec<Entry<T>>>, E2: Into<Vec<Entry<T>>>> From<(E1, E2)> for Dispatch<T> {
@@ -67,6 +270,7 @@ impl<T, E1: Into<Vec<Entry<T>>>, E2: Into<Vec<Entry<T>>>> From<(E1, E2)> for Dis
         Self {
             on_startup: value.0.into(),
             on_update: value.1.into(),
+            on_request: vec![],
         }
     }
 }
@@ -81,13 +285,33 @@ impl<EntryTy: ShaderEntry> Dispatch<EntryTy> {
         self.on_startup
             .iter()
             .map(|entry| entry.get_state(pipeline_cache, pipeline))
-            .all(|state| match state {
-                CachedPipelineState::Ok(_) => true,
-                CachedPipelineState::Err(e) => {
-                    panic!("Failed to load shader: {e}")
+            .all(|state| matches!(state, CachedPipelineState::Ok(_)))
+    }
+
+    /// The compile errors of every entry this node could dispatch
+    pub(super) fn errors<PipelineTy: Pipeline>(
+        &self,
+        requested: &[Entry<EntryTy>],
+        pipeline_cache: &PipelineCache,
+        pipeline: &PipelineTy,
+    ) -> Vec<ShaderError<EntryTy>>
+    where
+        EntryTy: Clone + PartialEq,
+    {
+        let mut errors: Vec<ShaderError<EntryTy>> = vec![];
+        for entry in self.entries(requested) {
+            if let Some(e) = compile_error(entry.get_state(pipeline_cache, pipeline)) {
+                let error = ShaderError {
+                    entry: Some(entry.entry.clone()),
+                    message: e.to_string(),
+                };
+                if !errors.contains(&error) {
+                    errors.push(error);
                 }
-                _ => false,
-            })
+            }
+        }
+
+        errors
     }
 
     pub(super) fn on_update_success<PipelineTy: Pipeline>(
@@ -101,27 +325,200 @@ impl<EntryTy: ShaderEntry> Dispatch<EntryTy> {
             .all(|state| matches!(state, CachedPipelineState::Ok(_)))
     }
 
-    pub(super) fn on_startup_dispatch<PipelineTy: Pipeline>(
+    /// Whether the workgroup count of every startup entry is known
+    pub(super) fn on_startup_resolved<PipelineTy>(
         &self,
+        sources: &WorkgroupSources<PipelineTy, EntryTy>,
+    ) -> bool {
+        self.on_startup.iter().all(|entry| {
+            let key = entry.entry.as_key();
+            entry.workgroup.resolve(key, sources).is_some()
+        })
+    }
+
+    /// Drops the requests whose pipeline failed to compile, they would otherwise stay queued forever
+    pub(super) fn discard_failed_requests<PipelineTy: Pipeline>(
+        requested: &mut Vec<Entry<EntryTy>>,
         pipeline_cache: &PipelineCache,
         pipeline: &PipelineTy,
-        pass: &mut ComputePass,
-        bind_group: &GenericBindGroup<PipelineTy>,
+    ) {
+        requested.retain(|entry| {
+            let Some(e) = compile_error(entry.get_state(pipeline_cache, pipeline)) else {
+                return true;
+            };
+            warn!(
+                "Discarding the requested dispatch of {}: {e}",
+                entry.entry.name()
+            );
+
+            false
+        });
+    }
+
+    /// Moves every request with a compiled pipeline into `on_request`, requests still compiling or
+    /// of disabled entries stay queued for a later frame
+    pub(super) fn take_ready_requests(
+        &mut self,
+        requested: &mut Vec<Entry<EntryTy>>,
+        pipelines: &CompiledPipelines,
+        disabled: &HashSet<usize>,
+    ) {
+        let (ready, pending) = requested.drain(..).partition(|entry| {
+            !disabled.contains(&entry.entry.as_key()) && pipelines.get(&entry.entry).is_some()
+        });
+        self.on_request = ready;
+        *requested = pending;
+    }
+
+    /// Every entry this node could dispatch
+    pub(super) fn entries<'a>(
+        &'a self,
+        requested: &'a [Entry<EntryTy>],
+    ) -> impl Iterator<Item = &'a Entry<EntryTy>> {
+        self.on_startup
+            .iter()
+            .chain(&self.on_update)
+            .chain(requested)
+    }
+
+    pub(super) fn on_startup_dispatch<PipelineTy: Pipeline>(
+        &self,
+        context: &DispatchContext<PipelineTy, EntryTy>,
+        recorder: &mut PassRecorder<impl RecordDiagnostics>,
     ) {
         for entry in self.on_startup.iter() {
-            entry.dispatch(pipeline_cache, pipeline, pass, bind_group);
+            entry.dispatch(context, recorder);
         }
     }
 
+    /// How many times every update entry is dispatched this frame
+    pub(super) fn update_counts(
+        &self,
+        frame: u64,
+        delta: Duration,
+        accumulated: &mut Vec<f64>,
+    ) -> Vec<u32> {
+        accumulated.resize(self.on_update.len(), 0.);
+        self.on_update
+            .iter()
+            .zip(accumulated)
+            .map(|(entry, accumulated)| {
+                if entry.run_if.as_ref().is_some_and(|c| !c.holds()) {
+                    return 0;
+                }
+                entry.schedule.dispatch_count(frame, delta, accumulated)
+            })
+            .collect()
+    }
+
+    /// Dispatches the update entries with more than `round` dispatches this frame
     pub(super) fn on_update_dispatch<PipelineTy: Pipeline>(
         &self,
-        pipeline_cache: &PipelineCache,
-        pipeline: &PipelineTy,
-        pass: &mut ComputePass,
-        bind_group: &GenericBindGroup<PipelineTy>,
+        context: &DispatchContext<PipelineTy, EntryTy>,
+        counts: &[u32],
+        round: u32,
+        recorder: &mut PassRecorder<impl RecordDiagnostics>,
+    ) {
+        for (entry, _) in self
+            .on_update
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| **count > round)
+        {
+            entry.dispatch(context, recorder);
+        }
+    }
+
+    pub(super) fn on_request_dispatch<PipelineTy: Pipeline>(
+        &self,
+        context: &DispatchContext<PipelineTy, EntryTy>,
+        recorder: &mut PassRecorder<impl RecordDiagnostics>,
     ) {
-        for entry in self.on_update.iter() {
-            entry.dispatch(pipeline_cache, pipeline, pass, bind_group);
+        for entry in self.on_request.iter() {
+            entry.dispatch(context, recorder);
         }
     }
+
+    /// How many rounds of dispatches the frame has, each round dispatches every entry at most once
+    /// and swaps the ping pong halves afterwards
+    ///
+    /// Startup and requested entries only dispatch in the first round
+    pub(super) fn rounds<PipelineTy>(
+        &self,
+        startup: bool,
+        counts: &[u32],
+        pipelines: &CompiledPipelines,
+        sources: &WorkgroupSources<PipelineTy, EntryTy>,
+    ) -> u32 {
+        let dispatchable = |entry: &Entry<EntryTy>| entry.dispatchable(pipelines, sources);
+        let first = startup && self.on_startup.iter().any(dispatchable)
+            || self.on_request.iter().any(dispatchable);
+        let updates = self
+            .on_update
+            .iter()
+            .zip(counts)
+            .filter(|(entry, _)| dispatchable(entry))
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+
+        updates.max(first as u32)
+    }
+}
+
+/// The error of a pipeline that failed to compile, ignoring the ones the pipeline cache retries
+fn compile_error(state: &CachedPipelineState) -> Option<&PipelineCacheError> {
+    match state {
+        // The pipeline cache retries these once the shader and its imports loaded
+        CachedPipelineState::Err(
+            PipelineCacheError::ShaderNotLoaded(_)
+            | PipelineCacheError::ShaderImportNotYetAvailable,
+        ) => None,
+        CachedPipelineState::Err(e) => Some(e),
+        _ => None,
+    }
+}
+
+/// Dispatches an entry a single time, on the next frame its pipeline is ready
+/// ```ignore
+/// fn on_change(mut dispatches: EventWriter<DispatchShader<HelloEntries>>) {
+///     dispatches.send(DispatchShader::new(HelloEntries::Update, (3, 1, 1)));
+/// }
+/// ```
+#[derive(Event, Clone, Debug)]
+pub struct DispatchShader<EntryTy> {
+    pub entry: EntryTy,
+    pub workgroups: Workgroups,
+}
+
+impl<T> DispatchShader<T> {
+    pub fn new(entry: T, workgroups: impl Into<Workgroups>) -> Self {
+        Self {
+            entry,
+            workgroups: workgroups.into(),
+        }
+    }
+}
+
+impl<T> From<DispatchShader<T>> for Entry<T> {
+    fn from(value: DispatchShader<T>) -> Self {
+        Self::new(value.entry, value.workgroups)
+    }
+}
+
+/// Render world queue of the extracted [`DispatchShader`] events
+#[derive(Resource)]
+pub(super) struct RequestedDispatches<EntryTy>(pub(super) Vec<Entry<EntryTy>>);
+
+impl<T> Default for RequestedDispatches<T> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+pub(super) fn extract_dispatches<EntryTy: Clone + Send + Sync + 'static>(
+    mut events: Extract<EventReader<DispatchShader<EntryTy>>>,
+    mut requested: ResMut<RequestedDispatches<EntryTy>>,
+) {
+    requested.0.extend(events.read().cloned().map(Entry::from));
 }
@@ -0,0 +1,289 @@
+mod exec;
+mod memory;
+mod value;
+
+use std::{fmt, marker::PhantomData};
+
+use bevy_asset::{Assets, io::file::FileAssetReader};
+use bevy_ecs::{
+    system::{Commands, Resource},
+    world::{CommandQueue, Mut, World},
+};
+use bevy_image::Image;
+use bevy_math::UVec3;
+use bevy_render::{render_resource::ShaderStages, storage::ShaderStorageBuffer};
+use naga::{
+    EntryPoint, Module, ShaderStage,
+    valid::{Capabilities, ValidationFlags, Validator},
+};
+
+use self::exec::BoundResource;
+use super::{
+    binding::ShaderDataDetails,
+    buffers::{BindingAsset, BufferGroup},
+    entries::ShaderEntry,
+    plugin::ShaderPlugin,
+    readback::ReadbackData,
+    source::ShaderSource,
+    status::ShaderError,
+    workgroups::{INDIRECT_ARGUMENTS_SIZE, Workgroups},
+};
+
+/// Runs compute entries on the CPU by interpreting the shader of a plugin, to test shader logic
+/// where there is no GPU
+///
+/// Covers 32 bit scalar, vector and matrix math, buffer loads and stores, atomics, and
+/// `textureLoad`/`textureStore` on storage textures. Invocations run one after another, so
+/// barriers are only supported with a `@workgroup_size` of 1. Out of bounds indices are clamped to
+/// the last element, and out of bounds texels read zero and are never written, as on most GPUs
+/// ```ignore
+/// let mut executor = CpuExecutor::new(plugin)?;
+/// executor.dispatch(&HelloEntries::Main, (3, 1, 1))?;
+/// let a: Vec<u32> = executor.get::<0, 0>().unwrap();
+/// ```
+pub struct CpuExecutor<DataTy, EntriesTy, BuffersTy, const B: usize, const E: usize> {
+    module: Module,
+    /// Holds the group of buffers and the assets behind it
+    world: World,
+    swapped: bool,
+    _phantom: PhantomData<(DataTy, EntriesTy, BuffersTy)>,
+}
+
+impl<
+    const B: usize,
+    const E: usize,
+    DataTy: Clone + ShaderDataDetails<B, E>,
+    EntriesTy: ShaderEntry + Clone + fmt::Debug,
+    BuffersTy: BufferGroup<DataTy, B> + Resource,
+> CpuExecutor<DataTy, EntriesTy, BuffersTy, B, E>
+{
+    /// Parses the shader of the plugin, and creates its buffers from the initial data
+    pub fn new(
+        plugin: ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>,
+    ) -> Result<Self, ShaderError<EntriesTy>> {
+        let data = plugin.initial_data().cloned().ok_or_else(|| {
+            setup_error("No initial data, set it with ShaderBuilder::initial_data")
+        })?;
+        let source = plugin.shader_source().ok_or_else(|| {
+            setup_error("No shader source, set one with ShaderBuilder::shader or #[shader(..)]")
+        })?;
+        let source = read_source(source).map_err(setup_error)?;
+        let module = naga::front::wgsl::parse_str(&source)
+            .map_err(|e| setup_error(e.emit_to_string(&source)))?;
+        // The interpreter relies on a well formed module, the capabilities are left to the GPU
+        Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|e| setup_error(e.emit_to_string(&source)))?;
+
+        let mut world = World::new();
+        world.init_resource::<Assets<ShaderStorageBuffer>>();
+        world.init_resource::<Assets<Image>>();
+        world.resource_scope(|world, mut buffers: Mut<Assets<ShaderStorageBuffer>>| {
+            world.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+                let mut queue = CommandQueue::default();
+                let mut commands = Commands::new(&mut queue, world);
+                BuffersTy::insert_resources(&mut commands, &mut buffers, &mut images, data);
+                queue.apply(world);
+            });
+        });
+
+        Ok(Self {
+            module,
+            world,
+            swapped: false,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Runs every invocation of the entry, the buffers are left unchanged when it fails
+    ///
+    /// Like a frame of the plugin, the ping pong halves swap after every dispatch
+    pub fn dispatch(
+        &mut self,
+        entry: &EntriesTy,
+        workgroups: impl Into<Workgroups>,
+    ) -> Result<(), ShaderError<EntriesTy>> {
+        let error = |message: String| ShaderError {
+            entry: Some(entry.clone()),
+            message,
+        };
+        let name = DataTy::entry_names()[entry.as_key()];
+        let entry_point = self
+            .module
+            .entry_points
+            .iter()
+            .find(|e| e.stage == ShaderStage::Compute && e.name == name)
+            .ok_or_else(|| error(format!("No compute entry point named {name}")))?;
+        let mut resources = self.resources();
+        let workgroups = resolve(workgroups.into(), entry_point, &resources).map_err(error)?;
+        exec::dispatch(&self.module, entry_point, workgroups, &mut resources).map_err(error)?;
+
+        let assets = self.world.resource::<BuffersTy>().assets(self.swapped);
+        for (asset, resource) in assets.into_iter().zip(resources) {
+            match asset {
+                BindingAsset::Buffer(handle) => {
+                    let mut buffers = self.world.resource_mut::<Assets<ShaderStorageBuffer>>();
+                    if let Some(buffer) = buffers.get_mut(&handle) {
+                        buffer.data = Some(resource.data);
+                    }
+                }
+                BindingAsset::Texture(handle) => {
+                    let mut images = self.world.resource_mut::<Assets<Image>>();
+                    if let Some(image) = images.get_mut(&handle) {
+                        image.data = resource.data;
+                    }
+                }
+            }
+        }
+        if BuffersTy::ping_pong() {
+            self.swapped = !self.swapped;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the resource bound at `GROUP`/`BINDING`, `None` when it is not `#[writeable]`
+    pub fn get<const GROUP: u32, const BINDING: u32>(
+        &self,
+    ) -> Option<<DataTy as ReadbackData<GROUP, BINDING>>::Output>
+    where
+        DataTy: ReadbackData<GROUP, BINDING>,
+    {
+        self.bytes(GROUP, BINDING).map(DataTy::read)
+    }
+
+    /// The raw bytes of the resource bound at `group`/`binding`, ping pong fields return their most
+    /// recently written half
+    pub fn bytes(&self, group: u32, binding: u32) -> Option<&[u8]> {
+        let index = bindings::<B, E, DataTy>().position(|index| index == (group, binding))?;
+        let asset = self.world.resource::<BuffersTy>().assets(self.swapped);
+        match asset.get(index)? {
+            BindingAsset::Buffer(handle) => {
+                let buffers = self.world.resource::<Assets<ShaderStorageBuffer>>();
+                buffers.get(handle)?.data.as_deref()
+            }
+            BindingAsset::Texture(handle) => {
+                let images = self.world.resource::<Assets<Image>>();
+                Some(&images.get(handle)?.data)
+            }
+        }
+    }
+
+    /// A copy of every bound resource, written back once the dispatch succeeded
+    fn resources(&self) -> Vec<BoundResource> {
+        let assets = self.world.resource::<BuffersTy>().assets(self.swapped);
+        let buffers = self.world.resource::<Assets<ShaderStorageBuffer>>();
+        let images = self.world.resource::<Assets<Image>>();
+        bindings::<B, E, DataTy>()
+            .zip(DataTy::element_sizes())
+            .zip(assets)
+            .map(|(((group, binding), element_size), asset)| {
+                let (data, size) = match asset {
+                    BindingAsset::Buffer(handle) => {
+                        let data = buffers.get(&handle).and_then(|b| b.data.clone());
+                        (data.unwrap_or_default(), None)
+                    }
+                    BindingAsset::Texture(handle) => match images.get(&handle) {
+                        Some(image) => {
+                            let size = image.texture_descriptor.size;
+                            let size =
+                                UVec3::new(size.width, size.height, size.depth_or_array_layers);
+                            (image.data.clone(), Some(size))
+                        }
+                        None => (vec![], Some(UVec3::ZERO)),
+                    },
+                };
+                BoundResource {
+                    group,
+                    binding,
+                    data,
+                    size,
+                    element_size,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The group and binding of every entry in `buffer_entries`
+fn bindings<const B: usize, const E: usize, DataTy: ShaderDataDetails<B, E>>()
+-> impl Iterator<Item = (u32, u32)> {
+    let layout = DataTy::buffer_entries(ShaderStages::COMPUTE);
+    let bindings: Vec<_> = layout.iter().map(|entry| entry.binding).collect();
+    DataTy::groups().into_iter().zip(bindings)
+}
+
+/// Workgroup counts as the compute node would dispatch them
+fn resolve(
+    workgroups: Workgroups,
+    entry: &EntryPoint,
+    resources: &[BoundResource],
+) -> Result<UVec3, String> {
+    let find = |group: u32, binding: u32| {
+        resources
+            .iter()
+            .find(|r| r.group == group && r.binding == binding)
+            .ok_or_else(|| format!("No binding {binding} in group {group}"))
+    };
+    let invocations = match workgroups {
+        Workgroups::Fixed(x, y, z) => return Ok(UVec3::new(x, y, z)),
+        Workgroups::Invocations(x, y, z) => UVec3::new(x, y, z),
+        Workgroups::Binding { group, binding } => {
+            let resource = find(group, binding)?;
+            resource.size.unwrap_or_else(|| {
+                let len = resource.data.len() as u64 / resource.element_size.max(1);
+                UVec3::new(len as u32, 1, 1)
+            })
+        }
+        Workgroups::Indirect {
+            group,
+            binding,
+            offset,
+        } => {
+            let data = &find(group, binding)?.data;
+            if offset % 4 != 0 {
+                return Err(format!("The indirect offset {offset} is not a multiple of 4"));
+            }
+            let offset = offset as usize;
+            let arguments = data
+                .get(offset..offset.saturating_add(INDIRECT_ARGUMENTS_SIZE as usize))
+                .ok_or_else(|| format!("No indirect arguments at offset {offset}"))?;
+            let counts = arguments
+                .chunks_exact(4)
+                .map(|count| u32::from_le_bytes([count[0], count[1], count[2], count[3]]));
+            return Ok(UVec3::from_array(
+                counts.collect::<Vec<_>>().try_into().unwrap_or_default(),
+            ));
+        }
+    };
+    let size = UVec3::from_array(entry.workgroup_size).max(UVec3::ONE);
+
+    Ok(UVec3::new(
+        invocations.x.div_ceil(size.x),
+        invocations.y.div_ceil(size.y),
+        invocations.z.div_ceil(size.z),
+    ))
+}
+
+/// Reads WGSL the way the asset server would, from the `assets` folder
+fn read_source(source: ShaderSource) -> Result<String, String> {
+    match source {
+        ShaderSource::Wgsl(source) => Ok(source.into_owned()),
+        ShaderSource::Path(path) if path.source().as_str().is_none() => {
+            let file = FileAssetReader::new("assets").root_path().join(path.path());
+            std::fs::read_to_string(&file)
+                .map_err(|e| format!("Cannot read {}: {e}", file.display()))
+        }
+        ShaderSource::Path(path) => Err(format!("Cannot read {path}, only the assets folder is")),
+        ShaderSource::Handle(_) => {
+            Err("Shader handles cannot be interpreted, use a path or WGSL source".to_string())
+        }
+    }
+}
+
+fn setup_error<EntriesTy>(message: impl Into<String>) -> ShaderError<EntriesTy> {
+    ShaderError {
+        entry: None,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,737 @@
+use std::collections::HashMap;
+
+use bevy_math::UVec3;
+use naga::{
+    AddressSpace, Arena, AtomicFunction, BinaryOperator, Binding, Block, BuiltIn, EntryPoint,
+    Expression, Function, GlobalVariable, Handle, ImageClass, ImageDimension, ImageQuery, Literal,
+    MathFunction, Module, Statement, StorageFormat, SwitchValue, Type, TypeInner,
+};
+
+use super::{
+    memory,
+    value::{self, Pointer, Value},
+};
+
+type Result<T> = std::result::Result<T, String>;
+
+/// The contents of a buffer or texture bound to the shader, written back once the dispatch finished
+pub(super) struct BoundResource {
+    pub group: u32,
+    pub binding: u32,
+    pub data: Vec<u8>,
+    /// Width, height and depth or layers of textures, `None` for buffers
+    pub size: Option<UVec3>,
+    /// Bytes of one element of a buffer
+    pub element_size: u64,
+}
+
+/// Runs every invocation of `workgroups` one after another, the resources are only written when
+/// every invocation succeeded
+pub(super) fn dispatch(
+    module: &Module,
+    entry: &EntryPoint,
+    workgroups: UVec3,
+    resources: &mut [BoundResource],
+) -> Result<()> {
+    let mut interpreter = Interpreter::new(module, resources)?;
+    interpreter.dispatch(entry, workgroups)?;
+    for (resource, slot) in resources.iter_mut().zip(interpreter.slots) {
+        resource.data = slot;
+    }
+
+    Ok(())
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Option<Value>),
+}
+
+#[derive(Clone, Copy)]
+struct Texture {
+    slot: usize,
+    size: UVec3,
+    dim: ImageDimension,
+    format: StorageFormat,
+}
+
+/// The ids of the invocation being interpreted
+struct Invocation {
+    global: UVec3,
+    local: UVec3,
+    local_index: u32,
+    workgroup: UVec3,
+    workgroups: UVec3,
+}
+
+struct Frame<'a> {
+    expressions: &'a Arena<Expression>,
+    arguments: Vec<Value>,
+    locals: Vec<Pointer>,
+    /// Every expression evaluated so far, emitted ones are evaluated again whenever they are emitted
+    values: Vec<Option<Value>>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(expressions: &'a Arena<Expression>, arguments: Vec<Value>) -> Self {
+        Self {
+            expressions,
+            arguments,
+            locals: vec![],
+            values: vec![None; expressions.len()],
+        }
+    }
+}
+
+struct Interpreter<'a> {
+    module: &'a Module,
+    /// Bound resources first, then workgroup and private variables, then function variables as a stack
+    slots: Vec<Vec<u8>>,
+    textures: Vec<Texture>,
+    /// What every bound global evaluates to, a pointer or a texture
+    globals: HashMap<Handle<GlobalVariable>, Value>,
+    workgroup: Vec<Handle<GlobalVariable>>,
+    private: Vec<Handle<GlobalVariable>>,
+    /// Invocations per workgroup
+    invocations: u32,
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(module: &'a Module, resources: &[BoundResource]) -> Result<Self> {
+        let mut interpreter = Self {
+            module,
+            slots: resources.iter().map(|r| r.data.clone()).collect(),
+            textures: vec![],
+            globals: HashMap::new(),
+            workgroup: vec![],
+            private: vec![],
+            invocations: 1,
+        };
+        for (handle, global) in module.global_variables.iter() {
+            let ty = &module.types[global.ty].inner;
+            match (global.space, &global.binding) {
+                (AddressSpace::WorkGroup, _) => interpreter.workgroup.push(handle),
+                (AddressSpace::Private, _) => interpreter.private.push(handle),
+                (_, Some(binding)) => {
+                    // Globals without a resource fail once they are used
+                    let Some(slot) = resources
+                        .iter()
+                        .position(|r| r.group == binding.group && r.binding == binding.binding)
+                    else {
+                        continue;
+                    };
+                    let value = interpreter.bind(slot, ty, resources[slot].size)?;
+                    interpreter.globals.insert(handle, value);
+                }
+                _ => {}
+            }
+        }
+        for handle in interpreter.workgroup.iter().chain(&interpreter.private) {
+            let ty = &module.types[module.global_variables[*handle].ty].inner;
+            let pointer = Pointer {
+                slot: interpreter.slots.len(),
+                offset: 0,
+                ty: ty.clone(),
+            };
+            interpreter.slots.push(vec![0; memory::size_of(ty, module)]);
+            interpreter.globals.insert(*handle, Value::Pointer(pointer));
+        }
+
+        Ok(interpreter)
+    }
+
+    fn bind(&mut self, slot: usize, ty: &TypeInner, size: Option<UVec3>) -> Result<Value> {
+        let (dim, class, size) = match (ty, size) {
+            (&TypeInner::Image { dim, class, .. }, Some(size)) => (dim, class, size),
+            (TypeInner::Image { .. }, None) => return Err("a buffer is bound to a texture".into()),
+            (_, Some(_)) => return Err("a texture is bound to a buffer".into()),
+            (ty, None) => {
+                return Ok(Value::Pointer(Pointer {
+                    slot,
+                    offset: 0,
+                    ty: ty.clone(),
+                }));
+            }
+        };
+        let ImageClass::Storage { format, .. } = class else {
+            return Err("only storage textures are supported".into());
+        };
+        let texels = size.x as usize * size.y as usize * size.z as usize;
+        if self.slots[slot].len() < texels * memory::texel_size(format)? {
+            return Err(format!(
+                "the data of a {size} texture is too short for {format:?}"
+            ));
+        }
+        self.textures.push(Texture {
+            slot,
+            size,
+            dim,
+            format,
+        });
+
+        Ok(Value::Texture(self.textures.len() - 1))
+    }
+
+    fn dispatch(&mut self, entry: &'a EntryPoint, workgroups: UVec3) -> Result<()> {
+        let size = UVec3::from_array(entry.workgroup_size);
+        self.invocations = size.element_product();
+        for workgroup in grid(workgroups) {
+            self.reset(&self.workgroup.clone(), false)?;
+            for local in grid(size) {
+                self.reset(&self.private.clone(), true)?;
+                let invocation = Invocation {
+                    global: workgroup * size + local,
+                    local,
+                    local_index: local.x + local.y * size.x + local.z * size.x * size.y,
+                    workgroup,
+                    workgroups,
+                };
+                let arguments = entry
+                    .function
+                    .arguments
+                    .iter()
+                    .map(|argument| {
+                        self.argument(argument.binding.as_ref(), argument.ty, &invocation)
+                    })
+                    .collect::<Result<_>>()?;
+                self.call(&entry.function, arguments)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets variables back to their initializer, or zero
+    fn reset(&mut self, globals: &[Handle<GlobalVariable>], initialize: bool) -> Result<()> {
+        for handle in globals {
+            let Some(Value::Pointer(pointer)) = self.globals.get(handle).cloned() else {
+                continue;
+            };
+            self.slots[pointer.slot].fill(0);
+            let init = self.module.global_variables[*handle].init;
+            if let Some(init) = init.filter(|_| initialize) {
+                let value = self.constant(init)?;
+                self.store(&pointer, &value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn argument(
+        &self,
+        binding: Option<&Binding>,
+        ty: Handle<Type>,
+        invocation: &Invocation,
+    ) -> Result<Value> {
+        let builtin = match binding {
+            Some(Binding::BuiltIn(builtin)) => builtin,
+            Some(binding) => return Err(format!("{binding:?} is not a compute builtin")),
+            // Builtins may be gathered in a struct
+            None => {
+                let TypeInner::Struct { members, .. } = &self.module.types[ty].inner else {
+                    return Err("entry arguments need a builtin".into());
+                };
+                return members
+                    .iter()
+                    .map(|m| self.argument(m.binding.as_ref(), m.ty, invocation))
+                    .collect::<Result<_>>()
+                    .map(Value::Composite);
+            }
+        };
+        let value = match builtin {
+            BuiltIn::GlobalInvocationId => uvec3(invocation.global),
+            BuiltIn::LocalInvocationId => uvec3(invocation.local),
+            BuiltIn::LocalInvocationIndex => Value::U32(invocation.local_index),
+            BuiltIn::WorkGroupId => uvec3(invocation.workgroup),
+            BuiltIn::NumWorkGroups => uvec3(invocation.workgroups),
+            builtin => return Err(format!("{builtin:?} is not supported")),
+        };
+
+        Ok(value)
+    }
+
+    fn call(&mut self, function: &'a Function, arguments: Vec<Value>) -> Result<Option<Value>> {
+        let module = self.module;
+        let stack = self.slots.len();
+        let mut frame = Frame::new(&function.expressions, arguments);
+        let mut flow = Ok(Flow::Next);
+        for (_, local) in function.local_variables.iter() {
+            let ty = &module.types[local.ty].inner;
+            let pointer = Pointer {
+                slot: self.slots.len(),
+                offset: 0,
+                ty: ty.clone(),
+            };
+            self.slots.push(vec![0; memory::size_of(ty, module)]);
+            frame.locals.push(pointer.clone());
+            if let Some(init) = local.init {
+                flow = self
+                    .eval(&mut frame, init)
+                    .and_then(|value| self.store(&pointer, &value))
+                    .map(|_| Flow::Next);
+            }
+        }
+        if flow.is_ok() {
+            flow = self.block(&mut frame, &function.body);
+        }
+        self.slots.truncate(stack);
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(None),
+        }
+    }
+
+    fn block(&mut self, frame: &mut Frame<'a>, block: &'a Block) -> Result<Flow> {
+        for statement in block.iter() {
+            match self.statement(frame, statement)? {
+                Flow::Next => {}
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(Flow::Next)
+    }
+
+    fn statement(&mut self, frame: &mut Frame<'a>, statement: &'a Statement) -> Result<Flow> {
+        match *statement {
+            Statement::Emit(ref range) => {
+                for handle in range.clone() {
+                    let value = self.evaluate(frame, handle)?;
+                    frame.values[handle.index()] = Some(value);
+                }
+            }
+            Statement::Block(ref block) => return self.block(frame, block),
+            Statement::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.eval(frame, condition)?.as_bool()?;
+                return self.block(frame, if condition { accept } else { reject });
+            }
+            Statement::Switch {
+                selector,
+                ref cases,
+            } => {
+                let selector = self.eval(frame, selector)?.as_i64()?;
+                let matches = |value: &SwitchValue| match *value {
+                    SwitchValue::I32(value) => value as i64 == selector,
+                    SwitchValue::U32(value) => value as i64 == selector,
+                    SwitchValue::Default => false,
+                };
+                let start = cases
+                    .iter()
+                    .position(|case| matches(&case.value))
+                    .or_else(|| {
+                        cases
+                            .iter()
+                            .position(|case| case.value == SwitchValue::Default)
+                    });
+                for case in &cases[start.unwrap_or(cases.len())..] {
+                    match self.block(frame, &case.body)? {
+                        Flow::Next if case.fall_through => {}
+                        Flow::Next | Flow::Break => break,
+                        flow => return Ok(flow),
+                    }
+                }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+            } => loop {
+                match self.block(frame, body)? {
+                    Flow::Break => break,
+                    Flow::Return(value) => return Ok(Flow::Return(value)),
+                    Flow::Next | Flow::Continue => {}
+                }
+                if let Flow::Return(value) = self.block(frame, continuing)? {
+                    return Ok(Flow::Return(value));
+                }
+                if let Some(condition) = break_if
+                    && self.eval(frame, condition)?.as_bool()?
+                {
+                    break;
+                }
+            },
+            Statement::Break => return Ok(Flow::Break),
+            Statement::Continue => return Ok(Flow::Continue),
+            Statement::Return { value } => {
+                let value = value.map(|value| self.eval(frame, value)).transpose()?;
+                return Ok(Flow::Return(value));
+            }
+            Statement::Barrier(_) => self.check_barrier()?,
+            Statement::Store { pointer, value } => {
+                let pointer = self.eval(frame, pointer)?;
+                let value = self.eval(frame, value)?;
+                self.store(as_pointer(&pointer)?, &value)?;
+            }
+            Statement::ImageStore {
+                image,
+                coordinate,
+                array_index,
+                value,
+            } => {
+                let texture = self.eval(frame, image)?;
+                let coordinate = self.eval(frame, coordinate)?;
+                let array_index = array_index.map(|i| self.eval(frame, i)).transpose()?;
+                let value = self.eval(frame, value)?;
+                let texture = *self.texture(&texture)?;
+                // Stores outside of the texture are discarded
+                if let Some(offset) = texel_offset(&texture, &coordinate, array_index.as_ref())? {
+                    let bytes = &mut self.slots[texture.slot][offset..];
+                    memory::write_texel(bytes, texture.format, &value)?;
+                }
+            }
+            Statement::Atomic {
+                pointer,
+                ref fun,
+                value,
+                result,
+            } => {
+                let pointer = self.eval(frame, pointer)?;
+                let pointer = as_pointer(&pointer)?;
+                let operand = self.eval(frame, value)?;
+                let old = self.load(pointer)?;
+                let (new, returned) = match *fun {
+                    AtomicFunction::Add => (binary(BinaryOperator::Add, &old, operand)?, old),
+                    AtomicFunction::Subtract => {
+                        (binary(BinaryOperator::Subtract, &old, operand)?, old)
+                    }
+                    AtomicFunction::And => (binary(BinaryOperator::And, &old, operand)?, old),
+                    AtomicFunction::ExclusiveOr => {
+                        (binary(BinaryOperator::ExclusiveOr, &old, operand)?, old)
+                    }
+                    AtomicFunction::InclusiveOr => {
+                        (binary(BinaryOperator::InclusiveOr, &old, operand)?, old)
+                    }
+                    AtomicFunction::Min => (
+                        value::math(MathFunction::Min, vec![old.clone(), operand])?,
+                        old,
+                    ),
+                    AtomicFunction::Max => (
+                        value::math(MathFunction::Max, vec![old.clone(), operand])?,
+                        old,
+                    ),
+                    AtomicFunction::Exchange { compare: None } => (operand, old),
+                    AtomicFunction::Exchange {
+                        compare: Some(compare),
+                    } => {
+                        let exchanged = self.eval(frame, compare)? == old;
+                        let new = if exchanged { operand } else { old.clone() };
+                        (new, Value::Composite(vec![old, Value::Bool(exchanged)]))
+                    }
+                };
+                self.store(pointer, &new)?;
+                if let Some(result) = result {
+                    frame.values[result.index()] = Some(returned);
+                }
+            }
+            Statement::WorkGroupUniformLoad { pointer, result } => {
+                self.check_barrier()?;
+                let pointer = self.eval(frame, pointer)?;
+                frame.values[result.index()] = Some(self.load(as_pointer(&pointer)?)?);
+            }
+            Statement::Call {
+                function,
+                ref arguments,
+                result,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.eval(frame, *argument))
+                    .collect::<Result<_>>()?;
+                let value = self.call(&self.module.functions[function], arguments)?;
+                if let Some(result) = result {
+                    frame.values[result.index()] = value;
+                }
+            }
+            Statement::Kill => return Err("discard is only allowed in fragment shaders".into()),
+            ref statement => return Err(format!("{statement:?} is not supported")),
+        }
+
+        Ok(Flow::Next)
+    }
+
+    /// Invocations run one after another, so they cannot wait for each other
+    fn check_barrier(&self) -> Result<()> {
+        if self.invocations > 1 {
+            return Err("barriers are only supported with a @workgroup_size of 1".into());
+        }
+        Ok(())
+    }
+
+    /// The value of an expression, which is only evaluated again once it is emitted again
+    fn eval(&mut self, frame: &mut Frame<'a>, handle: Handle<Expression>) -> Result<Value> {
+        if let Some(value) = &frame.values[handle.index()] {
+            return Ok(value.clone());
+        }
+        let value = self.evaluate(frame, handle)?;
+        frame.values[handle.index()] = Some(value.clone());
+
+        Ok(value)
+    }
+
+    fn evaluate(&mut self, frame: &mut Frame<'a>, handle: Handle<Expression>) -> Result<Value> {
+        let module = self.module;
+        let value = match frame.expressions[handle] {
+            Expression::Literal(literal) => literal_value(literal)?,
+            Expression::Constant(constant) => self.constant(module.constants[constant].init)?,
+            Expression::ZeroValue(ty) => memory::zero(&module.types[ty].inner, module)?,
+            Expression::Compose { ty, ref components } => {
+                let components = components.iter().map(|c| self.eval(frame, *c));
+                let mut components = components.collect::<Result<Vec<_>>>()?;
+                // Vectors can be composed from smaller vectors
+                if matches!(module.types[ty].inner, TypeInner::Vector { .. }) {
+                    components = components
+                        .into_iter()
+                        .flat_map(|component| match component {
+                            Value::Composite(inner) => inner,
+                            scalar => vec![scalar],
+                        })
+                        .collect();
+                }
+                Value::Composite(components)
+            }
+            Expression::Access { base, index } => {
+                let base = self.eval(frame, base)?;
+                // Negative indices wrap around like unsigned indices on the GPU, and are clamped
+                let index = self.eval(frame, index)?.as_i64()?;
+                self.access(base, usize::try_from(index).unwrap_or(usize::MAX))?
+            }
+            Expression::AccessIndex { base, index } => {
+                let base = self.eval(frame, base)?;
+                self.access(base, index as usize)?
+            }
+            Expression::Splat { size, value } => {
+                Value::Composite(vec![self.eval(frame, value)?; size as usize])
+            }
+            Expression::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let vector = self.eval(frame, vector)?;
+                let components = vector.components()?;
+                let swizzled = pattern[..size as usize].iter().map(|component| {
+                    let len = components.len();
+                    components.get(*component as usize).cloned().ok_or_else(|| {
+                        format!("swizzle component {component:?} is out of bounds for length {len}")
+                    })
+                });
+                Value::Composite(swizzled.collect::<Result<_>>()?)
+            }
+            Expression::FunctionArgument(index) => frame.arguments[index as usize].clone(),
+            Expression::GlobalVariable(global) => {
+                self.globals.get(&global).cloned().ok_or_else(|| {
+                    let name = module.global_variables[global].name.as_deref();
+                    format!("nothing is bound to {}", name.unwrap_or("a global"))
+                })?
+            }
+            Expression::LocalVariable(local) => Value::Pointer(frame.locals[local.index()].clone()),
+            Expression::Load { pointer } => {
+                let pointer = self.eval(frame, pointer)?;
+                self.load(as_pointer(&pointer)?)?
+            }
+            Expression::ImageLoad {
+                image,
+                coordinate,
+                array_index,
+                ..
+            } => {
+                let texture = self.eval(frame, image)?;
+                let coordinate = self.eval(frame, coordinate)?;
+                let array_index = array_index.map(|i| self.eval(frame, i)).transpose()?;
+                let texture = self.texture(&texture)?;
+                let bytes = match texel_offset(texture, &coordinate, array_index.as_ref())? {
+                    Some(offset) => &self.slots[texture.slot][offset..],
+                    // Out of bounds loads return zero
+                    None => &vec![0; memory::texel_size(texture.format)?],
+                };
+                memory::read_texel(bytes, texture.format)?
+            }
+            Expression::ImageQuery { image, query } => {
+                let texture = self.eval(frame, image)?;
+                let texture = self.texture(&texture)?;
+                let size = texture.size;
+                match query {
+                    ImageQuery::Size { .. } => match texture.dim {
+                        ImageDimension::D1 => Value::U32(size.x),
+                        ImageDimension::D2 | ImageDimension::Cube => {
+                            Value::Composite(vec![Value::U32(size.x), Value::U32(size.y)])
+                        }
+                        ImageDimension::D3 => uvec3(size),
+                    },
+                    ImageQuery::NumLayers => Value::U32(size.z),
+                    ImageQuery::NumLevels | ImageQuery::NumSamples => Value::U32(1),
+                }
+            }
+            Expression::Unary { op, expr } => value::unary(op, self.eval(frame, expr)?)?,
+            Expression::Binary { op, left, right } => {
+                let left = self.eval(frame, left)?;
+                value::binary(op, left, self.eval(frame, right)?)?
+            }
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition = self.eval(frame, condition)?;
+                let accept = self.eval(frame, accept)?;
+                value::select(condition, accept, self.eval(frame, reject)?)?
+            }
+            Expression::Relational { fun, argument } => {
+                value::relational(fun, self.eval(frame, argument)?)?
+            }
+            Expression::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let args = [Some(arg), arg1, arg2, arg3].into_iter().flatten();
+                let args = args.map(|arg| self.eval(frame, arg));
+                value::math(fun, args.collect::<Result<_>>()?)?
+            }
+            Expression::As {
+                expr,
+                kind,
+                convert,
+            } => value::cast(self.eval(frame, expr)?, kind, convert)?,
+            Expression::ArrayLength(array) => {
+                let pointer = self.eval(frame, array)?;
+                let pointer = as_pointer(&pointer)?;
+                Value::U32(memory::array_length(
+                    pointer,
+                    self.slots[pointer.slot].len(),
+                )?)
+            }
+            Expression::CallResult(_)
+            | Expression::AtomicResult { .. }
+            | Expression::WorkGroupUniformLoadResult { .. } => {
+                return Err("a result was used before its statement ran".into());
+            }
+            ref expression => return Err(format!("{expression:?} is not supported")),
+        };
+
+        Ok(value)
+    }
+
+    /// Evaluates a constant expression of the module
+    fn constant(&mut self, handle: Handle<Expression>) -> Result<Value> {
+        let mut frame = Frame::new(&self.module.global_expressions, vec![]);
+        self.eval(&mut frame, handle)
+    }
+
+    fn access(&self, base: Value, index: usize) -> Result<Value> {
+        match base {
+            Value::Pointer(pointer) => {
+                let len = self.slots[pointer.slot].len();
+                memory::element(&pointer, index, len, self.module).map(Value::Pointer)
+            }
+            Value::Composite(components) => {
+                let index = memory::clamp_index(index, components.len())?;
+                Ok(components[index].clone())
+            }
+            base => Err(format!("cannot index into {base:?}")),
+        }
+    }
+
+    /// The bytes from the pointer to the end of its slot, after checking they can hold its type
+    fn bytes(&self, pointer: &Pointer) -> Result<std::ops::Range<usize>> {
+        let len = self.slots[pointer.slot].len();
+        let end = pointer.offset + memory::size_of(&pointer.ty, self.module);
+        if end > len {
+            return Err(format!(
+                "reading {end} bytes out of a buffer of {len} bytes"
+            ));
+        }
+
+        Ok(pointer.offset..len)
+    }
+
+    fn load(&self, pointer: &Pointer) -> Result<Value> {
+        let range = self.bytes(pointer)?;
+        memory::read(&self.slots[pointer.slot][range], &pointer.ty, self.module)
+    }
+
+    fn store(&mut self, pointer: &Pointer, value: &Value) -> Result<()> {
+        let range = self.bytes(pointer)?;
+        memory::write(
+            &mut self.slots[pointer.slot][range],
+            &pointer.ty,
+            value,
+            self.module,
+        )
+    }
+
+    fn texture(&self, value: &Value) -> Result<&Texture> {
+        match value {
+            Value::Texture(index) => Ok(&self.textures[*index]),
+            value => Err(format!("expected a texture, found {value:?}")),
+        }
+    }
+}
+
+fn as_pointer(value: &Value) -> Result<&Pointer> {
+    match value {
+        Value::Pointer(pointer) => Ok(pointer),
+        value => Err(format!("expected a pointer, found {value:?}")),
+    }
+}
+
+fn binary(op: BinaryOperator, left: &Value, right: Value) -> Result<Value> {
+    value::binary(op, left.clone(), right)
+}
+
+fn literal_value(literal: Literal) -> Result<Value> {
+    match literal {
+        Literal::F32(v) => Ok(Value::F32(v)),
+        Literal::U32(v) => Ok(Value::U32(v)),
+        Literal::I32(v) => Ok(Value::I32(v)),
+        Literal::Bool(v) => Ok(Value::Bool(v)),
+        literal => Err(format!(
+            "{literal:?} is not supported, only 32 bit scalars are"
+        )),
+    }
+}
+
+fn uvec3(v: UVec3) -> Value {
+    Value::Composite(v.to_array().map(Value::U32).to_vec())
+}
+
+/// Every position in a grid of `size`, x first
+fn grid(size: UVec3) -> impl Iterator<Item = UVec3> {
+    (0..size.z).flat_map(move |z| {
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| UVec3::new(x, y, z)))
+    })
+}
+
+/// The byte offset of a texel, `None` when it is outside of the texture
+fn texel_offset(
+    texture: &Texture,
+    coordinate: &Value,
+    array_index: Option<&Value>,
+) -> Result<Option<usize>> {
+    let coordinate = match coordinate {
+        Value::Composite(components) => components.iter().map(Value::as_i64).collect(),
+        scalar => scalar.as_i64().map(|c| vec![c]),
+    }?;
+    let mut position = [0; 3];
+    position[..coordinate.len()].copy_from_slice(&coordinate);
+    if let Some(layer) = array_index {
+        position[2] = layer.as_i64()?;
+    }
+    let size = texture.size.to_array().map(i64::from);
+    if position.iter().zip(size).any(|(p, s)| *p < 0 || *p >= s) {
+        return Ok(None);
+    }
+    let texel = (position[2] * size[1] + position[1]) * size[0] + position[0];
+
+    Ok(Some(texel as usize * memory::texel_size(texture.format)?))
+}
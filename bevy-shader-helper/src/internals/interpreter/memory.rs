@@ -0,0 +1,362 @@
+use naga::{
+    ArraySize, Module, Scalar, ScalarKind, StorageFormat, TypeInner, VectorSize, proc::Alignment,
+};
+
+use super::value::{Pointer, Value};
+
+type Result<T> = std::result::Result<T, String>;
+
+/// Bytes of `ty` in memory, dynamically sized arrays take one element
+pub(super) fn size_of(ty: &TypeInner, module: &Module) -> usize {
+    ty.size(module.to_ctx()) as usize
+}
+
+/// Where the element at `index` of a pointed to vector, matrix, array or struct lives
+pub(super) fn element(
+    pointer: &Pointer,
+    index: usize,
+    slot_len: usize,
+    module: &Module,
+) -> Result<Pointer> {
+    let (offset, ty) = match pointer.ty {
+        TypeInner::Vector { size, scalar } => {
+            let index = clamp_index(index, size as usize)?;
+            (index * scalar.width as usize, TypeInner::Scalar(scalar))
+        }
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => {
+            let index = clamp_index(index, columns as usize)?;
+            let column = TypeInner::Vector { size: rows, scalar };
+            (index * column_stride(rows, scalar), column)
+        }
+        TypeInner::Array { base, size, stride } => {
+            let len = match size {
+                ArraySize::Constant(len) => len.get() as usize,
+                ArraySize::Dynamic => slot_len.saturating_sub(pointer.offset) / stride as usize,
+            };
+            let index = clamp_index(index, len)?;
+            (index * stride as usize, module.types[base].inner.clone())
+        }
+        TypeInner::Struct { ref members, .. } => {
+            let member = members.get(index).ok_or("struct member out of bounds")?;
+            (
+                member.offset as usize,
+                module.types[member.ty].inner.clone(),
+            )
+        }
+        ref ty => return Err(format!("cannot index into {ty:?}")),
+    };
+
+    Ok(Pointer {
+        slot: pointer.slot,
+        offset: pointer.offset + offset,
+        ty,
+    })
+}
+
+/// Out of bounds indices select the last element, like the `Restrict` bounds checks GPUs compile
+/// shaders with, only empty arrays have nothing to select
+pub(super) fn clamp_index(index: usize, len: usize) -> Result<usize> {
+    if len == 0 {
+        return Err(format!("index {index} is out of bounds for length 0"));
+    }
+    Ok(index.min(len - 1))
+}
+
+fn column_stride(rows: VectorSize, scalar: Scalar) -> usize {
+    (Alignment::from(rows) * scalar.width as u32) as usize
+}
+
+/// Elements of the dynamically sized array `pointer` points to
+pub(super) fn array_length(pointer: &Pointer, slot_len: usize) -> Result<u32> {
+    match pointer.ty {
+        TypeInner::Array {
+            size: ArraySize::Dynamic,
+            stride,
+            ..
+        } => Ok((slot_len.saturating_sub(pointer.offset) / stride as usize) as u32),
+        ref ty => Err(format!("cannot take the array length of {ty:?}")),
+    }
+}
+
+pub(super) fn read(bytes: &[u8], ty: &TypeInner, module: &Module) -> Result<Value> {
+    let value = match *ty {
+        TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => read_scalar(bytes, scalar)?,
+        TypeInner::Vector { size, scalar } => Value::Composite(
+            (0..size as usize)
+                .map(|i| read_scalar(&bytes[i * scalar.width as usize..], scalar))
+                .collect::<Result<_>>()?,
+        ),
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => {
+            let column = TypeInner::Vector { size: rows, scalar };
+            let stride = column_stride(rows, scalar);
+            Value::Composite(
+                (0..columns as usize)
+                    .map(|i| read(&bytes[i * stride..], &column, module))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        TypeInner::Array { base, size, stride } => {
+            let len = match size {
+                ArraySize::Constant(len) => len.get() as usize,
+                ArraySize::Dynamic => bytes.len() / stride as usize,
+            };
+            let base = &module.types[base].inner;
+            Value::Composite(
+                (0..len)
+                    .map(|i| read(&bytes[i * stride as usize..], base, module))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        TypeInner::Struct { ref members, .. } => Value::Composite(
+            members
+                .iter()
+                .map(|member| {
+                    let ty = &module.types[member.ty].inner;
+                    read(&bytes[member.offset as usize..], ty, module)
+                })
+                .collect::<Result<_>>()?,
+        ),
+        ref ty => return Err(format!("cannot load {ty:?}")),
+    };
+
+    Ok(value)
+}
+
+fn read_scalar(bytes: &[u8], scalar: Scalar) -> Result<Value> {
+    if scalar.kind == ScalarKind::Bool {
+        return Ok(Value::Bool(bytes[0] != 0));
+    }
+    let bytes: [u8; 4] = bytes
+        .get(..4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .filter(|_| scalar.width == 4)
+        .ok_or_else(|| format!("{scalar:?} is not supported, only 32 bit scalars are"))?;
+    let value = match scalar.kind {
+        ScalarKind::Float => Value::F32(f32::from_le_bytes(bytes)),
+        ScalarKind::Sint => Value::I32(i32::from_le_bytes(bytes)),
+        ScalarKind::Uint => Value::U32(u32::from_le_bytes(bytes)),
+        kind => return Err(format!("cannot load {kind:?} scalars")),
+    };
+
+    Ok(value)
+}
+
+pub(super) fn write(
+    bytes: &mut [u8],
+    ty: &TypeInner,
+    value: &Value,
+    module: &Module,
+) -> Result<()> {
+    match (ty, value) {
+        (TypeInner::Scalar(_) | TypeInner::Atomic(_), value) => write_scalar(bytes, value),
+        (&TypeInner::Vector { scalar, .. }, Value::Composite(components)) => components
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, component)| {
+                write_scalar(&mut bytes[i * scalar.width as usize..], component)
+            }),
+        (&TypeInner::Matrix { rows, scalar, .. }, Value::Composite(columns)) => {
+            let column = TypeInner::Vector { size: rows, scalar };
+            let stride = column_stride(rows, scalar);
+            columns
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, value)| write(&mut bytes[i * stride..], &column, value, module))
+        }
+        (&TypeInner::Array { base, stride, .. }, Value::Composite(elements)) => {
+            let base = &module.types[base].inner;
+            elements.iter().enumerate().try_for_each(|(i, value)| {
+                write(&mut bytes[i * stride as usize..], base, value, module)
+            })
+        }
+        (TypeInner::Struct { members, .. }, Value::Composite(values)) => {
+            members.iter().zip(values).try_for_each(|(member, value)| {
+                let ty = &module.types[member.ty].inner;
+                write(&mut bytes[member.offset as usize..], ty, value, module)
+            })
+        }
+        (ty, value) => Err(format!("cannot store {value:?} as {ty:?}")),
+    }
+}
+
+fn write_scalar(bytes: &mut [u8], value: &Value) -> Result<()> {
+    let encoded = match *value {
+        Value::Bool(v) => {
+            bytes[0] = v as u8;
+            return Ok(());
+        }
+        Value::F32(v) => v.to_le_bytes(),
+        Value::I32(v) => v.to_le_bytes(),
+        Value::U32(v) => v.to_le_bytes(),
+        ref value => return Err(format!("cannot store {value:?}")),
+    };
+    bytes[..4].copy_from_slice(&encoded);
+
+    Ok(())
+}
+
+/// The zero value of `ty`, which every variable without an initializer starts with
+pub(super) fn zero(ty: &TypeInner, module: &Module) -> Result<Value> {
+    read(&vec![0; size_of(ty, module)], ty, module)
+}
+
+/// How the channels of a storage texture format are encoded
+enum Channel {
+    Float,
+    Uint(usize),
+    Sint(usize),
+    Unorm(usize),
+    Snorm(usize),
+}
+
+/// The channels of every format, and the bytes of each
+fn texel_layout(format: StorageFormat) -> Result<(Channel, usize)> {
+    use StorageFormat as F;
+
+    let layout = match format {
+        F::R32Float => (Channel::Float, 1),
+        F::Rg32Float => (Channel::Float, 2),
+        F::Rgba32Float => (Channel::Float, 4),
+        F::R32Uint => (Channel::Uint(4), 1),
+        F::Rg32Uint => (Channel::Uint(4), 2),
+        F::Rgba32Uint => (Channel::Uint(4), 4),
+        F::R32Sint => (Channel::Sint(4), 1),
+        F::Rg32Sint => (Channel::Sint(4), 2),
+        F::Rgba32Sint => (Channel::Sint(4), 4),
+        F::R16Uint => (Channel::Uint(2), 1),
+        F::Rg16Uint => (Channel::Uint(2), 2),
+        F::Rgba16Uint => (Channel::Uint(2), 4),
+        F::R16Sint => (Channel::Sint(2), 1),
+        F::Rg16Sint => (Channel::Sint(2), 2),
+        F::Rgba16Sint => (Channel::Sint(2), 4),
+        F::R16Unorm => (Channel::Unorm(2), 1),
+        F::Rg16Unorm => (Channel::Unorm(2), 2),
+        F::Rgba16Unorm => (Channel::Unorm(2), 4),
+        F::R16Snorm => (Channel::Snorm(2), 1),
+        F::Rg16Snorm => (Channel::Snorm(2), 2),
+        F::Rgba16Snorm => (Channel::Snorm(2), 4),
+        F::R8Uint => (Channel::Uint(1), 1),
+        F::Rg8Uint => (Channel::Uint(1), 2),
+        F::Rgba8Uint => (Channel::Uint(1), 4),
+        F::R8Sint => (Channel::Sint(1), 1),
+        F::Rg8Sint => (Channel::Sint(1), 2),
+        F::Rgba8Sint => (Channel::Sint(1), 4),
+        F::R8Unorm => (Channel::Unorm(1), 1),
+        F::Rg8Unorm => (Channel::Unorm(1), 2),
+        F::Rgba8Unorm | F::Bgra8Unorm => (Channel::Unorm(1), 4),
+        F::R8Snorm => (Channel::Snorm(1), 1),
+        F::Rg8Snorm => (Channel::Snorm(1), 2),
+        F::Rgba8Snorm => (Channel::Snorm(1), 4),
+        format => return Err(format!("{format:?} textures are not supported")),
+    };
+
+    Ok(layout)
+}
+
+/// Bytes of a single texel
+pub(super) fn texel_size(format: StorageFormat) -> Result<usize> {
+    let (channel, channels) = texel_layout(format)?;
+    let bytes = match channel {
+        Channel::Float => 4,
+        Channel::Uint(bytes)
+        | Channel::Sint(bytes)
+        | Channel::Unorm(bytes)
+        | Channel::Snorm(bytes) => bytes,
+    };
+
+    Ok(bytes * channels)
+}
+
+/// Reads a texel into a `vec4`, filling missing channels like the GPU does
+pub(super) fn read_texel(bytes: &[u8], format: StorageFormat) -> Result<Value> {
+    let (channel, channels) = texel_layout(format)?;
+    let mut texel: Vec<Value> = match channel {
+        Channel::Float | Channel::Unorm(_) | Channel::Snorm(_) => {
+            vec![
+                Value::F32(0.),
+                Value::F32(0.),
+                Value::F32(0.),
+                Value::F32(1.),
+            ]
+        }
+        Channel::Uint(_) => vec![Value::U32(0), Value::U32(0), Value::U32(0), Value::U32(1)],
+        Channel::Sint(_) => vec![Value::I32(0), Value::I32(0), Value::I32(0), Value::I32(1)],
+    };
+    for (i, value) in texel.iter_mut().take(channels).enumerate() {
+        *value = match channel {
+            Channel::Float => Value::F32(f32::from_le_bytes(channel_bytes(bytes, i, 4))),
+            Channel::Uint(width) => Value::U32(u32::from_le_bytes(channel_bytes(bytes, i, width))),
+            Channel::Sint(width) => Value::I32(sign_extend(channel_bytes(bytes, i, width), width)),
+            Channel::Unorm(width) => {
+                let value = u32::from_le_bytes(channel_bytes(bytes, i, width));
+                Value::F32(value as f32 / unorm_max(width))
+            }
+            Channel::Snorm(width) => {
+                let value = sign_extend(channel_bytes(bytes, i, width), width);
+                Value::F32((value as f32 / snorm_max(width)).max(-1.))
+            }
+        };
+    }
+    if format == StorageFormat::Bgra8Unorm {
+        texel.swap(0, 2);
+    }
+
+    Ok(Value::Composite(texel))
+}
+
+/// Writes the channels of `format` from a `vec4`
+pub(super) fn write_texel(bytes: &mut [u8], format: StorageFormat, value: &Value) -> Result<()> {
+    let (channel, channels) = texel_layout(format)?;
+    let mut texel = value.components()?.to_vec();
+    if format == StorageFormat::Bgra8Unorm {
+        texel.swap(0, 2);
+    }
+    for (i, value) in texel.iter().take(channels).enumerate() {
+        let (encoded, width) = match channel {
+            Channel::Float => (value.as_f32()?.to_le_bytes(), 4),
+            // Integer formats keep the low bits, like a GPU writing to a narrower texel
+            Channel::Uint(width) | Channel::Sint(width) => {
+                (((value.as_i64()?) as u32).to_le_bytes(), width)
+            }
+            Channel::Unorm(width) => {
+                let value = value.as_f32()?.clamp(0., 1.) * unorm_max(width);
+                ((value.round() as u32).to_le_bytes(), width)
+            }
+            Channel::Snorm(width) => {
+                let value = value.as_f32()?.clamp(-1., 1.) * snorm_max(width);
+                ((value.round() as i32 as u32).to_le_bytes(), width)
+            }
+        };
+        bytes[i * width..(i + 1) * width].copy_from_slice(&encoded[..width]);
+    }
+
+    Ok(())
+}
+
+/// The little endian bytes of a channel, zero extended to 4 bytes
+fn channel_bytes(bytes: &[u8], index: usize, width: usize) -> [u8; 4] {
+    let mut channel = [0; 4];
+    channel[..width].copy_from_slice(&bytes[index * width..(index + 1) * width]);
+    channel
+}
+
+fn sign_extend(bytes: [u8; 4], width: usize) -> i32 {
+    let shift = 32 - width as u32 * 8;
+    (i32::from_le_bytes(bytes) << shift) >> shift
+}
+
+fn unorm_max(bytes: usize) -> f32 {
+    (u32::MAX >> (32 - bytes * 8)) as f32
+}
+
+fn snorm_max(bytes: usize) -> f32 {
+    (i32::MAX >> (32 - bytes * 8)) as f32
+}
@@ -0,0 +1,557 @@
+use naga::{
+    BinaryOperator, MathFunction, RelationalFunction, ScalarKind, TypeInner, UnaryOperator,
+};
+
+/// A value of the interpreted shader, vectors and matrices are composites of their components and
+/// columns
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Value {
+    Bool(bool),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    /// Vectors, matrices, arrays and structs
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+    /// A texture bound to the shader, by its index among the bound textures
+    Texture(usize),
+}
+
+/// A location in the memory of the interpreter, along with the type stored there
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Pointer {
+    pub slot: usize,
+    pub offset: usize,
+    pub ty: TypeInner,
+}
+
+type Result<T> = std::result::Result<T, String>;
+
+impl Value {
+    pub(super) fn as_bool(&self) -> Result<bool> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            value => Err(format!("expected a bool, found {value:?}")),
+        }
+    }
+
+    /// Reads signed and unsigned integers, as both can index and select
+    pub(super) fn as_i64(&self) -> Result<i64> {
+        match self {
+            Value::I32(value) => Ok(*value as i64),
+            Value::U32(value) => Ok(*value as i64),
+            value => Err(format!("expected an integer, found {value:?}")),
+        }
+    }
+
+    pub(super) fn as_f32(&self) -> Result<f32> {
+        match self {
+            Value::F32(value) => Ok(*value),
+            value => Err(format!("expected a float, found {value:?}")),
+        }
+    }
+
+    pub(super) fn components(&self) -> Result<&[Value]> {
+        match self {
+            Value::Composite(components) => Ok(components),
+            value => Err(format!("expected a vector or composite, found {value:?}")),
+        }
+    }
+
+    /// The value of the same scalar type as `self`
+    fn with_f64(&self, value: f64) -> Result<Value> {
+        match self {
+            Value::F32(_) => Ok(Value::F32(value as f32)),
+            Value::I32(_) => Ok(Value::I32(value as i32)),
+            Value::U32(_) => Ok(Value::U32(value as u32)),
+            value => Err(format!("expected a number, found {value:?}")),
+        }
+    }
+
+    fn is_matrix(&self) -> bool {
+        matches!(self, Value::Composite(columns) if matches!(columns.first(), Some(Value::Composite(_))))
+    }
+}
+
+/// Applies `f` to every component, scalar arguments apply to each component of vector arguments
+fn componentwise(args: &[Value], f: &dyn Fn(&[Value]) -> Result<Value>) -> Result<Value> {
+    let Some(len) = args.iter().find_map(|arg| match arg {
+        Value::Composite(components) => Some(components.len()),
+        _ => None,
+    }) else {
+        return f(args);
+    };
+    let components = (0..len).map(|i| {
+        let args: Vec<_> = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Composite(components) => components[i].clone(),
+                scalar => scalar.clone(),
+            })
+            .collect();
+        componentwise(&args, f)
+    });
+
+    Ok(Value::Composite(components.collect::<Result<_>>()?))
+}
+
+pub(super) fn unary(op: UnaryOperator, value: Value) -> Result<Value> {
+    componentwise(&[value], &|args| match (op, &args[0]) {
+        (UnaryOperator::Negate, Value::F32(v)) => Ok(Value::F32(-v)),
+        (UnaryOperator::Negate, Value::I32(v)) => Ok(Value::I32(v.wrapping_neg())),
+        (UnaryOperator::LogicalNot, Value::Bool(v)) => Ok(Value::Bool(!v)),
+        (UnaryOperator::BitwiseNot, Value::I32(v)) => Ok(Value::I32(!v)),
+        (UnaryOperator::BitwiseNot, Value::U32(v)) => Ok(Value::U32(!v)),
+        (op, value) => Err(format!("cannot apply {op:?} to {value:?}")),
+    })
+}
+
+pub(super) fn binary(op: BinaryOperator, left: Value, right: Value) -> Result<Value> {
+    if op == BinaryOperator::Multiply
+        && (left.is_matrix() || right.is_matrix())
+        && let Some(product) = matrix_product(&left, &right)?
+    {
+        return Ok(product);
+    }
+    componentwise(&[left, right], &|args| {
+        scalar_binary(op, &args[0], &args[1])
+    })
+}
+
+/// Matrix products, `None` for products with a scalar which apply to every component
+fn matrix_product(left: &Value, right: &Value) -> Result<Option<Value>> {
+    let (Value::Composite(l), Value::Composite(r)) = (left, right) else {
+        return Ok(None);
+    };
+    let product = match (left.is_matrix(), right.is_matrix()) {
+        // Sum of the columns, each scaled by a component of the vector
+        (true, false) => {
+            let mut sum = binary(BinaryOperator::Multiply, l[0].clone(), r[0].clone())?;
+            for (column, component) in l.iter().zip(r).skip(1) {
+                let scaled = binary(BinaryOperator::Multiply, column.clone(), component.clone())?;
+                sum = binary(BinaryOperator::Add, sum, scaled)?;
+            }
+            sum
+        }
+        (false, true) => Value::Composite(
+            r.iter()
+                .map(|column| dot(left, column))
+                .collect::<Result<_>>()?,
+        ),
+        _ => Value::Composite(
+            r.iter()
+                .map(|column| binary(BinaryOperator::Multiply, left.clone(), column.clone()))
+                .collect::<Result<_>>()?,
+        ),
+    };
+
+    Ok(Some(product))
+}
+
+fn scalar_binary(op: BinaryOperator, left: &Value, right: &Value) -> Result<Value> {
+    use BinaryOperator as Op;
+
+    let value = match (left, right) {
+        (Value::F32(l), Value::F32(r)) => {
+            let (l, r) = (*l, *r);
+            match op {
+                Op::Add => Value::F32(l + r),
+                Op::Subtract => Value::F32(l - r),
+                Op::Multiply => Value::F32(l * r),
+                Op::Divide => Value::F32(l / r),
+                // Truncated like WGSL's `%`
+                Op::Modulo => Value::F32(l % r),
+                _ => compare(op, l, r)?,
+            }
+        }
+        (Value::I32(l), Value::I32(r)) => {
+            let (l, r) = (*l, *r);
+            match op {
+                Op::Add => Value::I32(l.wrapping_add(r)),
+                Op::Subtract => Value::I32(l.wrapping_sub(r)),
+                Op::Multiply => Value::I32(l.wrapping_mul(r)),
+                // Dividing by zero or overflowing returns the dividend in WGSL
+                Op::Divide => Value::I32(l.checked_div(r).unwrap_or(l)),
+                Op::Modulo => Value::I32(l.checked_rem(r).unwrap_or(0)),
+                Op::And => Value::I32(l & r),
+                Op::InclusiveOr => Value::I32(l | r),
+                Op::ExclusiveOr => Value::I32(l ^ r),
+                _ => compare(op, l, r)?,
+            }
+        }
+        (Value::U32(l), Value::U32(r)) => {
+            let (l, r) = (*l, *r);
+            match op {
+                Op::Add => Value::U32(l.wrapping_add(r)),
+                Op::Subtract => Value::U32(l.wrapping_sub(r)),
+                Op::Multiply => Value::U32(l.wrapping_mul(r)),
+                Op::Divide => Value::U32(l.checked_div(r).unwrap_or(l)),
+                Op::Modulo => Value::U32(l.checked_rem(r).unwrap_or(0)),
+                Op::And => Value::U32(l & r),
+                Op::InclusiveOr => Value::U32(l | r),
+                Op::ExclusiveOr => Value::U32(l ^ r),
+                Op::ShiftLeft => Value::U32(l.wrapping_shl(r)),
+                Op::ShiftRight => Value::U32(l.wrapping_shr(r)),
+                _ => compare(op, l, r)?,
+            }
+        }
+        // Shifting a signed integer keeps its sign
+        (Value::I32(l), Value::U32(r)) => match op {
+            Op::ShiftLeft => Value::I32(l.wrapping_shl(*r)),
+            Op::ShiftRight => Value::I32(l.wrapping_shr(*r)),
+            _ => return Err(format!("cannot apply {op:?} to {left:?} and {right:?}")),
+        },
+        (Value::Bool(l), Value::Bool(r)) => {
+            let (l, r) = (*l, *r);
+            match op {
+                Op::And | Op::LogicalAnd => Value::Bool(l && r),
+                Op::InclusiveOr | Op::LogicalOr => Value::Bool(l || r),
+                Op::Equal => Value::Bool(l == r),
+                Op::NotEqual => Value::Bool(l != r),
+                _ => return Err(format!("cannot apply {op:?} to {left:?} and {right:?}")),
+            }
+        }
+        _ => return Err(format!("cannot apply {op:?} to {left:?} and {right:?}")),
+    };
+
+    Ok(value)
+}
+
+fn compare<T: PartialOrd>(op: BinaryOperator, l: T, r: T) -> Result<Value> {
+    let result = match op {
+        BinaryOperator::Equal => l == r,
+        BinaryOperator::NotEqual => l != r,
+        BinaryOperator::Less => l < r,
+        BinaryOperator::LessEqual => l <= r,
+        BinaryOperator::Greater => l > r,
+        BinaryOperator::GreaterEqual => l >= r,
+        op => return Err(format!("{op:?} is not supported on these operands")),
+    };
+
+    Ok(Value::Bool(result))
+}
+
+pub(super) fn select(condition: Value, accept: Value, reject: Value) -> Result<Value> {
+    match condition {
+        Value::Bool(condition) => Ok(if condition { accept } else { reject }),
+        condition => componentwise(&[condition, accept, reject], &|args| {
+            Ok(if args[0].as_bool()? {
+                args[1].clone()
+            } else {
+                args[2].clone()
+            })
+        }),
+    }
+}
+
+pub(super) fn relational(fun: RelationalFunction, value: Value) -> Result<Value> {
+    match fun {
+        RelationalFunction::All => value
+            .components()?
+            .iter()
+            .try_fold(true, |all, v| Ok(all && v.as_bool()?))
+            .map(Value::Bool),
+        RelationalFunction::Any => value
+            .components()?
+            .iter()
+            .try_fold(false, |any, v| Ok(any || v.as_bool()?))
+            .map(Value::Bool),
+        RelationalFunction::IsNan => componentwise(&[value], &|args| {
+            Ok(Value::Bool(args[0].as_f32()?.is_nan()))
+        }),
+        RelationalFunction::IsInf => componentwise(&[value], &|args| {
+            Ok(Value::Bool(args[0].as_f32()?.is_infinite()))
+        }),
+    }
+}
+
+/// Converts to `kind`, `convert` is `None` for bitcasts
+pub(super) fn cast(value: Value, kind: ScalarKind, convert: Option<u8>) -> Result<Value> {
+    if convert.is_some_and(|width| width != 4 && kind != ScalarKind::Bool) {
+        return Err("only 32 bit scalars are supported".to_string());
+    }
+    componentwise(&[value], &|args| {
+        let value = &args[0];
+        let converted = match (convert, kind, value) {
+            (None, ScalarKind::Float, Value::U32(v)) => Value::F32(f32::from_bits(*v)),
+            (None, ScalarKind::Float, Value::I32(v)) => Value::F32(f32::from_bits(*v as u32)),
+            (None, ScalarKind::Uint, Value::F32(v)) => Value::U32(v.to_bits()),
+            (None, ScalarKind::Sint, Value::F32(v)) => Value::I32(v.to_bits() as i32),
+            (_, ScalarKind::Float, Value::F32(v)) => Value::F32(*v),
+            (_, ScalarKind::Uint, Value::U32(v)) => Value::U32(*v),
+            (_, ScalarKind::Sint, Value::I32(v)) => Value::I32(*v),
+            (_, ScalarKind::Uint, Value::I32(v)) => Value::U32(*v as u32),
+            (_, ScalarKind::Sint, Value::U32(v)) => Value::I32(*v as i32),
+            // Rust's float to integer casts saturate and truncate, like WGSL's
+            (Some(_), ScalarKind::Float, Value::U32(v)) => Value::F32(*v as f32),
+            (Some(_), ScalarKind::Float, Value::I32(v)) => Value::F32(*v as f32),
+            (Some(_), ScalarKind::Uint, Value::F32(v)) => Value::U32(*v as u32),
+            (Some(_), ScalarKind::Sint, Value::F32(v)) => Value::I32(*v as i32),
+            (Some(_), ScalarKind::Bool, Value::F32(v)) => Value::Bool(*v != 0.),
+            (Some(_), ScalarKind::Bool, Value::U32(v)) => Value::Bool(*v != 0),
+            (Some(_), ScalarKind::Bool, Value::I32(v)) => Value::Bool(*v != 0),
+            (Some(_), ScalarKind::Bool, Value::Bool(v)) => Value::Bool(*v),
+            (Some(_), ScalarKind::Float, Value::Bool(v)) => Value::F32(*v as u32 as f32),
+            (Some(_), ScalarKind::Uint, Value::Bool(v)) => Value::U32(*v as u32),
+            (Some(_), ScalarKind::Sint, Value::Bool(v)) => Value::I32(*v as i32),
+            (_, kind, value) => return Err(format!("cannot convert {value:?} to {kind:?}")),
+        };
+        Ok(converted)
+    })
+}
+
+pub(super) fn math(fun: MathFunction, args: Vec<Value>) -> Result<Value> {
+    use MathFunction as F;
+
+    let float = |f: fn(f32) -> f32| componentwise(&args, &|a| Ok(Value::F32(f(a[0].as_f32()?))));
+    let float2 = |f: fn(f32, f32) -> f32| {
+        componentwise(&args, &|a| {
+            Ok(Value::F32(f(a[0].as_f32()?, a[1].as_f32()?)))
+        })
+    };
+    let float3 = |f: fn(f32, f32, f32) -> f32| {
+        componentwise(&args, &|a| {
+            Ok(Value::F32(f(
+                a[0].as_f32()?,
+                a[1].as_f32()?,
+                a[2].as_f32()?,
+            )))
+        })
+    };
+
+    match fun {
+        F::Abs => componentwise(&args, &|a| match a[0] {
+            Value::F32(v) => Ok(Value::F32(v.abs())),
+            Value::I32(v) => Ok(Value::I32(v.wrapping_abs())),
+            Value::U32(v) => Ok(Value::U32(v)),
+            ref v => Err(format!("cannot apply abs to {v:?}")),
+        }),
+        F::Min => componentwise(&args, &|a| min_max(&a[0], &a[1], false)),
+        F::Max => componentwise(&args, &|a| min_max(&a[0], &a[1], true)),
+        F::Clamp => componentwise(&args, &|a| {
+            min_max(&min_max(&a[0], &a[1], true)?, &a[2], false)
+        }),
+        F::Saturate => float(|v| v.clamp(0., 1.)),
+        F::Cos => float(f32::cos),
+        F::Cosh => float(f32::cosh),
+        F::Sin => float(f32::sin),
+        F::Sinh => float(f32::sinh),
+        F::Tan => float(f32::tan),
+        F::Tanh => float(f32::tanh),
+        F::Acos => float(f32::acos),
+        F::Asin => float(f32::asin),
+        F::Atan => float(f32::atan),
+        F::Atan2 => float2(f32::atan2),
+        F::Asinh => float(f32::asinh),
+        F::Acosh => float(f32::acosh),
+        F::Atanh => float(f32::atanh),
+        F::Radians => float(f32::to_radians),
+        F::Degrees => float(f32::to_degrees),
+        F::Ceil => float(f32::ceil),
+        F::Floor => float(f32::floor),
+        F::Round => float(f32::round_ties_even),
+        F::Fract => float(|v| v - v.floor()),
+        F::Trunc => float(f32::trunc),
+        F::Ldexp => componentwise(&args, &|a| {
+            Ok(Value::F32(
+                a[0].as_f32()? * 2f32.powi(a[1].as_i64()? as i32),
+            ))
+        }),
+        F::Exp => float(f32::exp),
+        F::Exp2 => float(f32::exp2),
+        F::Log => float(f32::ln),
+        F::Log2 => float(f32::log2),
+        F::Pow => float2(f32::powf),
+        F::Sqrt => float(f32::sqrt),
+        F::InverseSqrt => float(|v| 1. / v.sqrt()),
+        F::Fma => float3(f32::mul_add),
+        F::Mix => float3(|a, b, t| a * (1. - t) + b * t),
+        F::Step => float2(|edge, x| if edge <= x { 1. } else { 0. }),
+        F::SmoothStep => float3(|low, high, x| {
+            let t = ((x - low) / (high - low)).clamp(0., 1.);
+            t * t * (3. - 2. * t)
+        }),
+        F::Sign => componentwise(&args, &|a| match a[0] {
+            Value::F32(0.) => Ok(Value::F32(0.)),
+            Value::F32(v) => Ok(Value::F32(v.signum())),
+            Value::I32(v) => Ok(Value::I32(v.signum())),
+            ref v => Err(format!("cannot apply sign to {v:?}")),
+        }),
+        F::Dot => dot(&args[0], &args[1]),
+        F::Cross => {
+            let (a, b) = (args[0].components()?, args[1].components()?);
+            let [ax, ay, az] = [&a[0], &a[1], &a[2]].map(Value::as_f32);
+            let [bx, by, bz] = [&b[0], &b[1], &b[2]].map(Value::as_f32);
+            let (ax, ay, az, bx, by, bz) = (ax?, ay?, az?, bx?, by?, bz?);
+            Ok(Value::Composite(vec![
+                Value::F32(ay * bz - az * by),
+                Value::F32(az * bx - ax * bz),
+                Value::F32(ax * by - ay * bx),
+            ]))
+        }
+        F::Length => length(&args[0]).map(Value::F32),
+        F::Distance => {
+            let difference = binary(BinaryOperator::Subtract, args[0].clone(), args[1].clone())?;
+            length(&difference).map(Value::F32)
+        }
+        F::Normalize => {
+            let length = Value::F32(length(&args[0])?);
+            binary(BinaryOperator::Divide, args[0].clone(), length)
+        }
+        F::FaceForward => {
+            let facing = dot(&args[1], &args[2])?.as_f32()? < 0.;
+            if facing {
+                Ok(args[0].clone())
+            } else {
+                unary(UnaryOperator::Negate, args[0].clone())
+            }
+        }
+        F::Reflect => {
+            let (e1, e2) = (&args[0], &args[1]);
+            let scale = Value::F32(2. * dot(e2, e1)?.as_f32()?);
+            let offset = binary(BinaryOperator::Multiply, scale, e2.clone())?;
+            binary(BinaryOperator::Subtract, e1.clone(), offset)
+        }
+        F::Refract => {
+            let (e1, e2, eta) = (&args[0], &args[1], args[2].as_f32()?);
+            let cos = dot(e2, e1)?.as_f32()?;
+            let k = 1. - eta * eta * (1. - cos * cos);
+            if k < 0. {
+                return binary(BinaryOperator::Multiply, e1.clone(), Value::F32(0.));
+            }
+            let incident = binary(BinaryOperator::Multiply, Value::F32(eta), e1.clone())?;
+            let normal = Value::F32(eta * cos + k.sqrt());
+            let normal = binary(BinaryOperator::Multiply, normal, e2.clone())?;
+            binary(BinaryOperator::Subtract, incident, normal)
+        }
+        F::Transpose => {
+            let columns = args[0].components()?;
+            let rows = columns
+                .first()
+                .map_or(Ok(0), |c| c.components().map(<[_]>::len))?;
+            let transposed = (0..rows).map(|row| {
+                let row = columns
+                    .iter()
+                    .map(|column| Ok(column.components()?[row].clone()));
+                row.collect::<Result<_>>().map(Value::Composite)
+            });
+            transposed.collect::<Result<_>>().map(Value::Composite)
+        }
+        F::Determinant => {
+            let columns = args[0].components()?;
+            let matrix = columns
+                .iter()
+                .map(|column| column.components()?.iter().map(Value::as_f32).collect())
+                .collect::<Result<Vec<Vec<f32>>>>()?;
+            Ok(Value::F32(determinant(&matrix)))
+        }
+        F::CountTrailingZeros => bits(&args, u32::trailing_zeros),
+        F::CountLeadingZeros => bits(&args, u32::leading_zeros),
+        F::CountOneBits => bits(&args, u32::count_ones),
+        F::ReverseBits => bits(&args, u32::reverse_bits),
+        F::FirstTrailingBit => bits(&args, |v| match v {
+            0 => u32::MAX,
+            v => v.trailing_zeros(),
+        }),
+        F::FirstLeadingBit => componentwise(&args, &|a| match a[0] {
+            Value::U32(0) => Ok(Value::U32(u32::MAX)),
+            Value::U32(v) => Ok(Value::U32(31 - v.leading_zeros())),
+            Value::I32(0 | -1) => Ok(Value::I32(-1)),
+            // The first bit which differs from the sign bit
+            Value::I32(v) if v < 0 => Ok(Value::I32(31 - (!v).leading_zeros() as i32)),
+            Value::I32(v) => Ok(Value::I32(31 - v.leading_zeros() as i32)),
+            ref v => Err(format!("cannot apply firstLeadingBit to {v:?}")),
+        }),
+        F::ExtractBits => componentwise(&args, &|a| {
+            let offset = (a[1].as_i64()? as u32).min(32);
+            let count = (a[2].as_i64()? as u32).min(32 - offset);
+            if count == 0 {
+                return a[0].with_f64(0.);
+            }
+            match a[0] {
+                Value::U32(v) => Ok(Value::U32((v >> offset) & mask(count))),
+                // Shifted up and back down again to extend the sign of the extracted bits
+                Value::I32(v) => Ok(Value::I32((v << (32 - offset - count)) >> (32 - count))),
+                ref v => Err(format!("cannot apply extractBits to {v:?}")),
+            }
+        }),
+        F::InsertBits => componentwise(&args, &|a| {
+            let offset = (a[2].as_i64()? as u32).min(32);
+            let count = (a[3].as_i64()? as u32).min(32 - offset);
+            if count == 0 {
+                return Ok(a[0].clone());
+            }
+            let mask = mask(count) << offset;
+            match (&a[0], &a[1]) {
+                (Value::U32(e), Value::U32(new)) => {
+                    Ok(Value::U32((e & !mask) | ((new << offset) & mask)))
+                }
+                (Value::I32(e), Value::I32(new)) => {
+                    let inserted = (*e as u32 & !mask) | (((*new as u32) << offset) & mask);
+                    Ok(Value::I32(inserted as i32))
+                }
+                (e, new) => Err(format!("cannot apply insertBits to {e:?} and {new:?}")),
+            }
+        }),
+        fun => Err(format!("{fun:?} is not supported")),
+    }
+}
+
+fn min_max(a: &Value, b: &Value, max: bool) -> Result<Value> {
+    match (a, b) {
+        (Value::F32(a), Value::F32(b)) => Ok(Value::F32(if max { a.max(*b) } else { a.min(*b) })),
+        (Value::I32(a), Value::I32(b)) => Ok(Value::I32(if max { *a.max(b) } else { *a.min(b) })),
+        (Value::U32(a), Value::U32(b)) => Ok(Value::U32(if max { *a.max(b) } else { *a.min(b) })),
+        (a, b) => Err(format!("cannot compare {a:?} and {b:?}")),
+    }
+}
+
+fn mask(count: u32) -> u32 {
+    u32::MAX.checked_shr(32 - count).unwrap_or(0)
+}
+
+/// Applies a bit operation to unsigned and signed integers alike
+fn bits(args: &[Value], f: fn(u32) -> u32) -> Result<Value> {
+    componentwise(args, &|a| match a[0] {
+        Value::U32(v) => Ok(Value::U32(f(v))),
+        Value::I32(v) => Ok(Value::I32(f(v as u32) as i32)),
+        ref v => Err(format!("expected an integer, found {v:?}")),
+    })
+}
+
+fn dot(a: &Value, b: &Value) -> Result<Value> {
+    let products = binary(BinaryOperator::Multiply, a.clone(), b.clone())?;
+    let mut components = products.components()?.iter().cloned();
+    let first = components
+        .next()
+        .ok_or("cannot apply dot to empty vectors")?;
+
+    components.try_fold(first, |sum, v| binary(BinaryOperator::Add, sum, v))
+}
+
+fn length(value: &Value) -> Result<f32> {
+    match value {
+        Value::F32(v) => Ok(v.abs()),
+        value => Ok(dot(value, value)?.as_f32()?.sqrt()),
+    }
+}
+
+/// Laplace expansion along the first column, matrices are at most 4x4
+fn determinant(columns: &[Vec<f32>]) -> f32 {
+    if columns.len() == 1 {
+        return columns[0][0];
+    }
+    (0..columns.len())
+        .map(|row| {
+            let minor: Vec<Vec<f32>> = columns[1..]
+                .iter()
+                .map(|column| {
+                    let kept = column.iter().enumerate().filter(|(r, _)| *r != row);
+                    kept.map(|(_, v)| *v).collect()
+                })
+                .collect();
+            let sign = if row % 2 == 0 { 1. } else { -1. };
+            sign * columns[0][row] * determinant(&minor)
+        })
+        .sum()
+}
@@ -1,58 +1,78 @@
 use std::marker::PhantomData;
 
-use bevy_asset::DirectAssetAccessExt;
-use bevy_ecs::{
-    system::Resource,
-    world::{FromWorld, World},
-};
+use bevy_asset::Handle;
+use bevy_ecs::{system::Resource, world::World};
 use bevy_render::{
-    render_resource::{BindGroupLayout, CachedComputePipelineId, PipelineCache, ShaderStages},
+    render_resource::{
+        BindGroupLayout, CachedComputePipelineId, PipelineCache, Shader, ShaderStages,
+    },
     renderer::RenderDevice,
 };
 
-use super::{binding::ShaderDataDetails, entries::ShaderEntry};
+use super::{
+    binding::{ShaderDataDetails, group_count, group_entries},
+    entries::ShaderEntry,
+};
 
 pub trait Pipeline {
     fn label() -> Option<&'static str> {
         None
     }
-    fn layout(&self) -> &BindGroupLayout;
+    /// One layout per bind group, in group order
+    fn layouts(&self) -> &[BindGroupLayout];
     fn get_id<EntryTy: ShaderEntry>(&self, entry: &EntryTy) -> CachedComputePipelineId;
+    /// Names the compute pass and debug group of the entry
+    fn entry_label<EntryTy: ShaderEntry>(&self, _entry: &EntryTy) -> Option<&'static str> {
+        None
+    }
 }
 
 #[derive(Resource)]
 pub struct ComputePipeline<const B: usize, const E: usize, DataTy> {
-    pub layout: BindGroupLayout,
+    pub layouts: Vec<BindGroupLayout>,
     pub entries: [CachedComputePipelineId; E],
+    pub shader: Handle<Shader>,
     _phantom: PhantomData<DataTy>,
 }
 
-impl<const B: usize, const E: usize, DataTy> Pipeline for ComputePipeline<B, E, DataTy> {
-    fn layout(&self) -> &BindGroupLayout {
-        &self.layout
+impl<const B: usize, const E: usize, DataTy: ShaderDataDetails<B, E>> Pipeline
+    for ComputePipeline<B, E, DataTy>
+{
+    fn layouts(&self) -> &[BindGroupLayout] {
+        &self.layouts
     }
 
     fn get_id<EntryTy: ShaderEntry>(&self, entry: &EntryTy) -> CachedComputePipelineId {
         self.entries[entry.as_key()]
     }
+
+    fn entry_label<EntryTy: ShaderEntry>(&self, entry: &EntryTy) -> Option<&'static str> {
+        DataTy::entry_labels()[entry.as_key()]
+    }
 }
 
-impl<const B: usize, const E: usize, DataTy: ShaderDataDetails<B, E>> FromWorld
-    for ComputePipeline<B, E, DataTy>
+impl<const B: usize, const E: usize, DataTy: ShaderDataDetails<B, E>>
+    ComputePipeline<B, E, DataTy>
 {
-    fn from_world(world: &mut World) -> Self {
+    pub fn new(world: &World, shader: Handle<Shader>) -> Self {
         let render_device = world.resource::<RenderDevice>();
-        let layout = render_device.create_bind_group_layout(
-            DataTy::bind_group_label(),
-            &DataTy::buffer_entries(ShaderStages::COMPUTE),
-        );
+        let buffer_entries = DataTy::buffer_entries(ShaderStages::COMPUTE);
+        let groups = DataTy::groups();
+        let layouts: Vec<_> = (0..group_count(&groups))
+            .map(|group| {
+                let entries: Vec<_> = group_entries(&buffer_entries, &groups, group)
+                    .copied()
+                    .collect();
+                render_device.create_bind_group_layout(DataTy::bind_group_label(), &entries)
+            })
+            .collect();
 
-        let shader = world.load_asset("shaders/hello.wgsl");
         let pipeline_cache = world.resource::<PipelineCache>();
-        let entries = DataTy::entries(pipeline_cache, layout.clone(), shader);
+        let entries = DataTy::entries(pipeline_cache, &layouts, shader.clone());
         Self {
-            layout,
+            layouts,
             entries,
+            shader,
             _phantom: Default::default(),
         }
     }
@@ -1,32 +1,51 @@
-use std::{fmt, hash::Hash, marker::PhantomData, sync::Arc};
+use std::{any::type_name, fmt, hash::Hash, marker::PhantomData, sync::Arc};
 
-use bevy_app::{App, Plugin, PreStartup};
+use bevy_app::{App, Last, Plugin, PreStartup};
 use bevy_asset::Assets;
 use bevy_ecs::{
-    prelude::{not, resource_exists},
     schedule::IntoSystemConfigs,
     system::{Commands, ResMut, Resource},
 };
 use bevy_image::Image;
 use bevy_render::{
-    Render, RenderApp, RenderSet, extract_resource::ExtractResource, render_graph::RenderGraph,
+    ExtractSchedule, Render, RenderApp, RenderSet,
+    diagnostic::RenderDiagnosticsPlugin,
+    extract_component::ExtractComponentPlugin,
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    render_graph::RenderGraph,
+    render_resource::ShaderStages,
     storage::ShaderStorageBuffer,
 };
+use tracing::{error, warn};
 
 use crate::{BuildableShader, ShaderBuilder};
 
 use super::{
-    binding::{GenericBindGroup, ShaderDataDetails, prepare_bind_group},
-    buffers::BufferGroup,
-    compute::ComputeNode,
-    entries::{Dispatch, ShaderEntry},
+    binding::{ShaderDataDetails, prepare_bind_group},
+    buffers::{
+        BufferGroup, PingPongFront, PingPongReadback, extract_ping_pong_front, retarget_readbacks,
+    },
+    compute::{ComputeNode, HotReload},
+    control::ShaderControl,
+    entries::{Dispatch, DispatchShader, RequestedDispatches, ShaderEntry, extract_dispatches},
     label::ShaderLabel,
     pipeline::ComputePipeline,
+    schedule::{DispatchConditions, evaluate_conditions, extract_conditions},
+    source::ShaderSource,
+    status::{ShaderError, ShaderReport, ShaderStatus, extract_status},
+    workgroups::{
+        BindingExtents, INDIRECT_ARGUMENTS_SIZE, IndirectBuffers, ReflectedShader, WorkgroupSizes,
+        Workgroups, extract_workgroup_sizes, prepare_extents, prepare_indirect_buffers,
+    },
 };
 
 pub struct ShaderPlugin<DataTy, EntriesTy, BuffersTy, const B: usize, const E: usize> {
-    initial_data: Arc<DataTy>,
+    /// Missing data is reported through [`ShaderStatus`] once the plugin is added
+    initial_data: Option<Arc<DataTy>>,
     entry_dispatches: Dispatch<EntriesTy>,
+    shader: Option<ShaderSource>,
+    hot_reload: HotReload,
+    gpu_timings: bool,
     _buffers_phantom: PhantomData<BuffersTy>,
 }
 
@@ -40,39 +59,202 @@ impl<
 > Plugin for ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>
 {
     fn build(&self, app: &mut App) {
+        // Registered even when misconfigured, so systems using them keep working
+        app.add_event::<ShaderError<EntriesTy>>();
+        app.add_event::<DispatchShader<EntriesTy>>();
+        let Some(initial_data) = self.initial_data.clone() else {
+            return report_setup_error::<EntriesTy>(
+                app,
+                "No initial data, set it with ShaderBuilder::initial_data",
+            );
+        };
+        if let Some(message) = self.invalid_workgroups(&initial_data) {
+            return report_setup_error::<EntriesTy>(app, &message);
+        }
+        app.init_resource::<ShaderStatus<EntriesTy>>()
+            .init_resource::<ShaderControl<EntriesTy>>()
+            .add_plugins(ExtractResourcePlugin::<ShaderControl<EntriesTy>>::default());
         BuffersTy::create_resource_extractor_plugins(app);
+        if BuffersTy::ping_pong() {
+            app.init_resource::<PingPongFront<BuffersTy>>()
+                .add_plugins(ExtractComponentPlugin::<PingPongReadback<BuffersTy>>::default());
+        }
         app.add_systems(
             PreStartup,
-            create_setup::<B, DataTy, BuffersTy>(self.initial_data.clone()),
+            create_setup::<B, DataTy, BuffersTy>(initial_data),
         );
+
+        // Evaluated last and extracted with the frame, so the render world sees its conditions
+        let conditions: Vec<_> = self
+            .entry_dispatches
+            .on_update
+            .iter()
+            .filter_map(|entry| entry.run_if.clone())
+            .collect();
+        if !conditions.is_empty() {
+            app.insert_resource(DispatchConditions::<EntriesTy>::new(conditions))
+                .add_systems(Last, evaluate_conditions::<EntriesTy>);
+        }
     }
 
     fn finish(&self, app: &mut App) {
+        if self.initial_data.is_none() {
+            return;
+        }
+        if self.gpu_timings && !app.is_plugin_added::<RenderDiagnosticsPlugin>() {
+            warn!(
+                "GPU timings are enabled but nothing is recorded without the RenderDiagnosticsPlugin"
+            );
+        }
+        let Some(shader) = self.shader_source() else {
+            return report_setup_error::<EntriesTy>(
+                app,
+                "No shader source, set one with ShaderBuilder::shader or #[shader(..)]",
+            );
+        };
+        let shader = shader.load(app.world_mut(), type_name::<DataTy>());
+
         let render_app = app.sub_app_mut(RenderApp);
         // debug!("Preparing render resources");
+        let pipeline = ComputePipeline::<B, E, DataTy>::new(render_app.world(), shader.clone());
         render_app
-            .init_resource::<ComputePipeline<B, E, DataTy>>()
+            .init_resource::<RequestedDispatches<EntriesTy>>()
+            .init_resource::<ShaderReport<EntriesTy>>()
+            .init_resource::<WorkgroupSizes<EntriesTy>>()
+            .insert_resource(ReflectedShader::<EntriesTy>::new(
+                shader,
+                &DataTy::entry_names(),
+            ))
+            .init_resource::<BindingExtents<ComputePipeline<B, E, DataTy>>>()
+            .init_resource::<IndirectBuffers<ComputePipeline<B, E, DataTy>>>()
             .add_systems(
-                Render,
-                prepare_bind_group::<B, _, ComputePipeline<B, E, DataTy>, BuffersTy>
-                    .in_set(RenderSet::PrepareBindGroups)
-                    .run_if(not(resource_exists::<
-                        GenericBindGroup<ComputePipeline<B, E, DataTy>>,
-                    >)),
+                ExtractSchedule,
+                (
+                    extract_dispatches::<EntriesTy>,
+                    extract_status::<EntriesTy>,
+                    extract_conditions::<EntriesTy>,
+                    extract_workgroup_sizes::<EntriesTy>,
+                ),
             );
+        if BuffersTy::ping_pong() {
+            render_app
+                .init_resource::<PingPongFront<BuffersTy>>()
+                .add_systems(ExtractSchedule, extract_ping_pong_front::<BuffersTy>)
+                .add_systems(
+                    Render,
+                    // Before the readback buffers are prepared
+                    retarget_readbacks::<BuffersTy>
+                        .in_set(RenderSet::Prepare)
+                        .before(RenderSet::PrepareResources),
+                );
+        }
+        render_app.insert_resource(pipeline).add_systems(
+            Render,
+            (
+                (
+                    prepare_extents::<B, E, DataTy, ComputePipeline<B, E, DataTy>, BuffersTy>,
+                    prepare_indirect_buffers::<B, _, ComputePipeline<B, E, DataTy>, BuffersTy>,
+                )
+                    .in_set(RenderSet::PrepareResources),
+                prepare_bind_group::<B, _, ComputePipeline<B, E, DataTy>, BuffersTy>
+                    .in_set(RenderSet::PrepareBindGroups),
+            ),
+        );
 
         render_app
             .world_mut()
             .resource_mut::<RenderGraph>()
             .add_node(
                 ShaderLabel::<EntriesTy>::new(),
-                ComputeNode::<ComputePipeline<B, E, DataTy>, EntriesTy>::new(
+                ComputeNode::<ComputePipeline<B, E, DataTy>, EntriesTy, BuffersTy>::new(
                     self.entry_dispatches.clone(),
+                    self.hot_reload,
+                    self.gpu_timings,
                 ),
             );
     }
 }
 
+impl<const B: usize, const E: usize, DataTy, EntriesTy, BuffersTy>
+    ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>
+where
+    DataTy: Clone + ShaderDataDetails<B, E>,
+    EntriesTy: ShaderEntry + fmt::Debug,
+    BuffersTy: BufferGroup<DataTy, B>,
+{
+    pub(super) fn initial_data(&self) -> Option<&DataTy> {
+        self.initial_data.as_deref()
+    }
+
+    /// The source set on the builder, or else the one set with `#[shader(..)]`
+    pub(super) fn shader_source(&self) -> Option<ShaderSource> {
+        self.shader.clone().or_else(DataTy::shader)
+    }
+
+    /// Describes the first entry sized by a binding the data or buffers do not have, or dispatched
+    /// with arguments outside of its `#[indirect]` buffer
+    fn invalid_workgroups(&self, data: &DataTy) -> Option<String> {
+        let layout = DataTy::buffer_entries(ShaderStages::COMPUTE);
+        let bindings: Vec<_> = DataTy::groups()
+            .into_iter()
+            .zip(layout.iter().map(|entry| entry.binding))
+            .collect();
+        let indirect = BuffersTy::indirect_bindings();
+        let sizes = BuffersTy::indirect_sizes(data);
+        self.entry_dispatches
+            .entries(&[])
+            .find_map(|entry| match entry.workgroup {
+                Workgroups::Binding { group, binding } if !bindings.contains(&(group, binding)) => {
+                    Some(format!(
+                        "No binding {binding} in group {group} to size the workgroups of {:?}",
+                        entry.entry
+                    ))
+                }
+                Workgroups::Indirect { group, binding, .. }
+                    if !indirect.contains(&(group, binding)) =>
+                {
+                    Some(format!(
+                        "No #[indirect] buffer at binding {binding} in group {group} to dispatch {:?}",
+                        entry.entry
+                    ))
+                }
+                Workgroups::Indirect { offset, .. } if offset % 4 != 0 => Some(format!(
+                    "The indirect offset {offset} of {:?} is not a multiple of 4",
+                    entry.entry
+                )),
+                Workgroups::Indirect {
+                    group,
+                    binding,
+                    offset,
+                } => {
+                    let index = indirect.iter().position(|i| *i == (group, binding))?;
+                    let size = *sizes.get(index)?;
+                    (offset.saturating_add(INDIRECT_ARGUMENTS_SIZE) > size).then(|| {
+                        format!(
+                            "The indirect arguments of {:?} at offset {offset} do not fit in the {size} bytes at binding {binding} in group {group}",
+                            entry.entry
+                        )
+                    })
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Leaves the plugin without any systems, so the app keeps running without the shader
+fn report_setup_error<EntriesTy: Clone + fmt::Debug + Send + Sync + 'static>(
+    app: &mut App,
+    message: &str,
+) {
+    let error = ShaderError {
+        entry: None,
+        message: message.to_string(),
+    };
+    error!("{error}");
+    app.insert_resource(ShaderStatus::<EntriesTy>::Failed(vec![error.clone()]));
+    app.world_mut().send_event(error);
+}
+
 fn create_setup<const B: usize, DataTy: Clone, BuffersTy: BufferGroup<DataTy, B>>(
     d: Arc<DataTy>,
 ) -> impl Fn(Commands, ResMut<Assets<ShaderStorageBuffer>>, ResMut<Assets<Image>>) {
@@ -85,16 +267,17 @@ impl<const B: usize, const E: usize, DataTy, EntriesTy, BuffersTy>
     BuildableShader<DataTy, EntriesTy> for ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>
 {
     fn from_builder(builder: ShaderBuilder<Self, DataTy, EntriesTy>) -> Self {
-        let Some(initial_data) = builder.initial_data else {
-            todo!()
-        };
-        let Some(entry_dispatches) = builder.dispatches else {
-            todo!()
-        };
+        // Without dispatches the shader only runs through DispatchShader events
+        let entry_dispatches = builder
+            .dispatches
+            .unwrap_or_else(|| (vec![], vec![]).into());
 
         Self {
-            initial_data: Arc::new(initial_data),
+            initial_data: builder.initial_data.map(Arc::new),
             entry_dispatches,
+            shader: builder.shader,
+            hot_reload: builder.hot_reload,
+            gpu_timings: builder.gpu_timings,
             _buffers_phantom: PhantomData,
         }
     }
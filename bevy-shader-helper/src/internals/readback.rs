@@ -0,0 +1,94 @@
+use bevy_asset::Assets;
+use bevy_ecs::{
+    component::{Component, ComponentHooks, StorageType},
+    observer::Trigger,
+    system::{Query, Res},
+};
+use bevy_image::{Image, TextureFormatPixelInfo};
+use bevy_render::{
+    gpu_readback::{Readback, ReadbackComplete},
+    render_resource::{
+        ShaderType,
+        encase::{self, internal::CreateFrom},
+    },
+    renderer::RenderDevice,
+};
+use bytemuck::Pod;
+use tracing::warn;
+
+/// What the readback of the field bound at `GROUP`/`BINDING` decodes into, implemented by the
+/// `ShaderDataDetails` derive for every field the shader can write
+pub trait ReadbackData<const GROUP: u32, const BINDING: u32> {
+    type Output;
+    fn read(bytes: &[u8]) -> Self::Output;
+}
+
+pub fn read_shader_type<DataTy: ShaderType + CreateFrom>(bytes: &[u8]) -> DataTy {
+    encase::StorageBuffer::new(bytes)
+        .create()
+        .expect("Failed to read readback data")
+}
+
+/// Texels are tightly packed, unlike the padded elements `read_shader_type` decodes
+pub fn read_texels<TexelTy: Pod>(bytes: &[u8]) -> Vec<TexelTy> {
+    bytemuck::pod_collect_to_vec(bytes)
+}
+
+type ReadbackCallback = Box<dyn FnMut(&[u8]) + Send + Sync>;
+
+/// Decodes every [`ReadbackComplete`] of its entity and hands the data to a callback, spawned
+/// alongside the [`Readback`] by the `readback_*` helpers of the `BufferGroup` derive
+pub struct OnReadback(Option<ReadbackCallback>);
+
+impl OnReadback {
+    pub fn new<DataTy: 'static>(
+        read: fn(&[u8]) -> DataTy,
+        mut callback: impl FnMut(DataTy) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Some(Box::new(move |bytes| callback(read(bytes)))))
+    }
+}
+
+impl Component for OnReadback {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            let Some(mut callback) = world
+                .get_mut::<OnReadback>(entity)
+                .and_then(|mut on_readback| on_readback.0.take())
+            else {
+                return;
+            };
+            world.commands().entity(entity).observe(
+                move |trigger: Trigger<ReadbackComplete>,
+                      readbacks: Query<&Readback>,
+                      images: Res<Assets<Image>>| {
+                    let bytes = &trigger.event().0;
+                    // Ping pong readbacks are retargeted in the render world, to a half of the same size
+                    match readbacks.get(trigger.entity()) {
+                        Ok(Readback::Texture(handle)) => match images.get(handle) {
+                            Some(image) => callback(&texel_bytes(bytes, image)),
+                            None => warn!(
+                                "Dropped the readback of {handle:?}, its size is unknown without the image in the main world"
+                            ),
+                        },
+                        _ => callback(bytes),
+                    }
+                },
+            );
+        });
+    }
+}
+
+// Texture readbacks pad every row to COPY_BYTES_PER_ROW_ALIGNMENT, typed data only wants the texels
+fn texel_bytes(bytes: &[u8], image: &Image) -> Vec<u8> {
+    let row = image.width() as usize * image.texture_descriptor.format.pixel_size();
+    let padded_row = RenderDevice::align_copy_bytes_per_row(row);
+    bytes
+        .chunks(padded_row)
+        .take(image.height() as usize)
+        .flat_map(|r| &r[..row])
+        .copied()
+        .collect()
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy_app::{App, PluginsState};
+use bevy_asset::AssetPlugin;
+use bevy_core::{FrameCountPlugin, TaskPoolPlugin};
+use bevy_ecs::{system::Resource, world::World};
+use bevy_render::{
+    RenderPlugin,
+    extract_resource::ExtractResource,
+    renderer::{
+        RenderAdapter, RenderAdapterInfo, RenderDevice, RenderInstance, RenderQueue, WgpuWrapper,
+    },
+    settings::{RenderCreation, WgpuSettings, WgpuSettingsPriority},
+    texture::ImagePlugin,
+};
+use bevy_tasks::{block_on, tick_global_task_pools_on_main_thread};
+use bevy_time::TimePlugin;
+use bevy_window::{ExitCondition, WindowPlugin};
+use wgpu::{
+    Backends, DeviceDescriptor, DeviceType, Features, Instance, InstanceDescriptor,
+    RequestAdapterOptions,
+};
+
+use super::{
+    binding::ShaderDataDetails,
+    buffers::BufferGroup,
+    control::ShaderControl,
+    entries::ShaderEntry,
+    plugin::ShaderPlugin,
+    readback::{OnReadback, ReadbackData},
+    status::{ShaderError, ShaderStatus},
+};
+
+/// Runs a shader plugin in a headless app without a window, and returns what its writeable buffers
+/// hold once it finished
+/// ```ignore
+/// let results = ComputeRunner::new(plugin).iterations(10).run()?;
+/// let a: Vec<u32> = results.get::<0, 0>().unwrap();
+/// ```
+pub struct ComputeRunner<DataTy, EntriesTy, BuffersTy, const B: usize, const E: usize> {
+    plugin: ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>,
+    iterations: u32,
+    timeout: Duration,
+    settings: WgpuSettings,
+}
+
+impl<
+    const B: usize,
+    const E: usize,
+    DataTy: Send + Sync + 'static + Clone + ShaderDataDetails<B, E>,
+    EntriesTy: Send + Sync + 'static + ShaderEntry + Clone + Eq + Hash + fmt::Debug,
+    BuffersTy: Send + Sync + 'static + BufferGroup<DataTy, B> + Resource + ExtractResource,
+> ComputeRunner<DataTy, EntriesTy, BuffersTy, B, E>
+{
+    pub fn new(plugin: ShaderPlugin<DataTy, EntriesTy, BuffersTy, B, E>) -> Self {
+        Self {
+            plugin,
+            iterations: 1,
+            timeout: Duration::from_secs(60),
+            settings: WgpuSettings::default(),
+        }
+    }
+
+    /// Frames of update entries dispatched after the startup entries, their schedules still apply
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+
+        self
+    }
+
+    /// How long to wait for the shader to compile, and for the results to be read back
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+
+    /// The device is created from the first adapter matching the settings, `constrained_limits` are
+    /// not applied
+    pub fn settings(mut self, settings: WgpuSettings) -> Self {
+        self.settings = settings;
+
+        self
+    }
+
+    /// Blocks until every writeable buffer was read back, or returns what stopped the shader
+    pub fn run(self) -> Result<ComputeResults<DataTy>, RunnerError<EntriesTy>> {
+        let render_creation = render_creation(&self.settings)?;
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            FrameCountPlugin,
+            TimePlugin,
+            WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+            AssetPlugin::default(),
+            RenderPlugin {
+                render_creation,
+                synchronous_pipeline_compilation: true,
+            },
+            ImagePlugin::default(),
+            self.plugin,
+        ));
+        // What the default runner does before the first update
+        while app.plugins_state() == PluginsState::Adding {
+            tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        let deadline = Instant::now() + self.timeout;
+        // Nothing is dispatched until the pipelines compiled, so every frame after is counted
+        match app
+            .world_mut()
+            .get_resource_mut::<ShaderControl<EntriesTy>>()
+        {
+            Some(mut control) => control.pause(),
+            None => {
+                let errors = status_errors(app.world()).unwrap_or_default();
+                return Err(RunnerError::Shader(errors));
+            }
+        }
+        update_until(&mut app, deadline, |world| {
+            matches!(
+                world.get_resource::<ShaderStatus<EntriesTy>>(),
+                Some(ShaderStatus::Ready)
+            )
+        })?;
+
+        // The startup entries take the first frame
+        app.world_mut()
+            .resource_mut::<ShaderControl<EntriesTy>>()
+            .step(self.iterations + 1);
+        for _ in 0..=self.iterations {
+            app.update();
+        }
+
+        let bytes = Arc::new(Mutex::new(HashMap::new()));
+        let readbacks = app.world().resource::<BuffersTy>().readbacks();
+        let count = readbacks.len();
+        for (index, readback, retarget) in readbacks {
+            let bytes = bytes.clone();
+            let on_readback = OnReadback::new(
+                |data: &[u8]| data.to_vec(),
+                move |data| {
+                    let mut bytes = bytes.lock().expect("Readback results poisoned");
+                    bytes.entry(index).or_insert(data);
+                },
+            );
+            let mut entity = app.world_mut().spawn((readback, on_readback));
+            if let Some(retarget) = retarget {
+                entity.insert(retarget);
+            }
+        }
+        update_until(&mut app, deadline, |_| {
+            bytes.lock().expect("Readback results poisoned").len() == count
+        })?;
+
+        let bytes = bytes.lock().expect("Readback results poisoned").clone();
+        Ok(ComputeResults {
+            bytes,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// The final contents of every `#[writeable]` buffer of a [`ComputeRunner`]
+pub struct ComputeResults<DataTy> {
+    bytes: HashMap<(u32, u32), Vec<u8>>,
+    _phantom: PhantomData<fn() -> DataTy>,
+}
+
+impl<DataTy> ComputeResults<DataTy> {
+    /// Decodes the buffer bound at `GROUP`/`BINDING`, `None` when it is not `#[writeable]`
+    pub fn get<const GROUP: u32, const BINDING: u32>(
+        &self,
+    ) -> Option<<DataTy as ReadbackData<GROUP, BINDING>>::Output>
+    where
+        DataTy: ReadbackData<GROUP, BINDING>,
+    {
+        self.bytes(GROUP, BINDING).map(DataTy::read)
+    }
+
+    /// The raw bytes of the buffer bound at `group`/`binding`, textures hold the tightly packed rows
+    /// of their first layer
+    pub fn bytes(&self, group: u32, binding: u32) -> Option<&[u8]> {
+        self.bytes.get(&(group, binding)).map(Vec::as_slice)
+    }
+}
+
+/// Why a [`ComputeRunner`] did not produce results
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunnerError<EntryTy> {
+    /// Neither a GPU nor a software adapter matches the settings
+    NoAdapter,
+    /// The adapter refused to create a device with the requested features and limits
+    Device(String),
+    /// The shader failed to compile, or the plugin is misconfigured
+    Shader(Vec<ShaderError<EntryTy>>),
+    /// The shader did not compile, or its results were not read back, before the timeout
+    Timeout,
+}
+
+impl<T: fmt::Debug> fmt::Display for RunnerError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "No GPU or software adapter found"),
+            Self::Device(message) => write!(f, "Failed to create a device: {message}"),
+            Self::Shader(errors) => {
+                let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            Self::Timeout => write!(f, "Timed out before the shader finished"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for RunnerError<T> {}
+
+/// Falls back to a software adapter when there is no GPU, the adapter is requested once and handed
+/// to the render plugin together with its device
+fn render_creation<EntryTy>(
+    settings: &WgpuSettings,
+) -> Result<RenderCreation, RunnerError<EntryTy>> {
+    let instance = Instance::new(InstanceDescriptor {
+        backends: settings.backends.unwrap_or(Backends::all()),
+        flags: settings.instance_flags,
+        dx12_shader_compiler: settings.dx12_shader_compiler.clone(),
+        gles_minor_version: settings.gles3_minor_version,
+    });
+    let adapter = [false, true]
+        .into_iter()
+        .find_map(|force_fallback_adapter| {
+            let options = RequestAdapterOptions {
+                power_preference: settings.power_preference,
+                force_fallback_adapter,
+                compatible_surface: None,
+            };
+            block_on(instance.request_adapter(&options))
+        })
+        .ok_or(RunnerError::NoAdapter)?;
+
+    // The features and limits the render plugin would request from the adapter
+    let (mut features, limits) = match settings.priority {
+        WgpuSettingsPriority::Functionality => {
+            let mut features = adapter.features();
+            if adapter.get_info().device_type == DeviceType::DiscreteGpu {
+                features -= Features::MAPPABLE_PRIMARY_BUFFERS;
+            }
+            features -= Features::RAY_QUERY | Features::RAY_TRACING_ACCELERATION_STRUCTURE;
+            (features, adapter.limits())
+        }
+        _ => (Features::empty(), settings.limits.clone()),
+    };
+    if let Some(disabled) = settings.disabled_features {
+        features -= disabled;
+    }
+    features |= settings.features;
+    let descriptor = DeviceDescriptor {
+        label: settings.device_label.as_deref(),
+        required_features: features,
+        required_limits: limits,
+        memory_hints: settings.memory_hints.clone(),
+    };
+    let (device, queue) =
+        block_on(adapter.request_device(&descriptor, settings.trace_path.as_deref()))
+            .map_err(|e| RunnerError::Device(e.to_string()))?;
+
+    Ok(RenderCreation::manual(
+        RenderDevice::from(device),
+        RenderQueue(Arc::new(WgpuWrapper::new(queue))),
+        RenderAdapterInfo(WgpuWrapper::new(adapter.get_info())),
+        RenderAdapter(Arc::new(WgpuWrapper::new(adapter))),
+        RenderInstance(Arc::new(WgpuWrapper::new(instance))),
+    ))
+}
+
+fn update_until<EntriesTy: Clone + Send + Sync + 'static>(
+    app: &mut App,
+    deadline: Instant,
+    mut done: impl FnMut(&World) -> bool,
+) -> Result<(), RunnerError<EntriesTy>> {
+    while Instant::now() < deadline {
+        app.update();
+        if let Some(errors) = status_errors(app.world()) {
+            return Err(RunnerError::Shader(errors));
+        }
+        if done(app.world()) {
+            return Ok(());
+        }
+    }
+
+    Err(RunnerError::Timeout)
+}
+
+fn status_errors<EntriesTy: Clone + Send + Sync + 'static>(
+    world: &World,
+) -> Option<Vec<ShaderError<EntriesTy>>> {
+    match world.get_resource::<ShaderStatus<EntriesTy>>() {
+        Some(ShaderStatus::Failed(errors)) => Some(errors.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use bevy_ecs::{
+    schedule::{BoxedCondition, Condition},
+    system::{IntoSystem, Res, Resource},
+    world::{Mut, World},
+};
+use bevy_render::Extract;
+use tracing::warn;
+
+/// How often an update entry is dispatched
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DispatchSchedule {
+    #[default]
+    EveryFrame,
+    /// Once every `n` frames
+    EveryNFrames(u32),
+    /// `n` times every frame, ping pong halves swap between the dispatches
+    Iterations(u32),
+    /// `hz` times per second of virtual time, independent of the frame rate, frames which fell
+    /// behind catch up with at most `max_substeps` dispatches
+    FixedRate { hz: f64, max_substeps: u32 },
+}
+
+impl DispatchSchedule {
+    /// Dispatches due this frame, `accumulated` carries the virtual time not yet dispatched by a fixed rate
+    pub(super) fn dispatch_count(&self, frame: u64, delta: Duration, accumulated: &mut f64) -> u32 {
+        match *self {
+            DispatchSchedule::EveryFrame => 1,
+            DispatchSchedule::EveryNFrames(n) => frame.is_multiple_of(n.max(1) as u64) as u32,
+            DispatchSchedule::Iterations(n) => n,
+            DispatchSchedule::FixedRate { hz, max_substeps } => {
+                let step = 1. / hz;
+                *accumulated += delta.as_secs_f64();
+                let steps = (*accumulated / step).floor();
+                // Steps over the limit are dropped instead of piling up
+                *accumulated -= steps * step;
+                let steps = steps as u32;
+                if steps > max_substeps {
+                    warn!(
+                        "A fixed rate of {hz} Hz fell behind, dropping {} dispatches",
+                        steps - max_substeps
+                    );
+                }
+                steps.min(max_substeps)
+            }
+        }
+    }
+}
+
+struct ConditionSystem {
+    system: BoxedCondition,
+    initialized: bool,
+}
+
+/// A main world run condition, evaluated at the end of every frame and extracted with it
+#[derive(Clone)]
+pub struct RunCondition {
+    system: Arc<Mutex<ConditionSystem>>,
+    evaluated: Arc<AtomicBool>,
+    holds: Arc<AtomicBool>,
+}
+
+impl RunCondition {
+    pub fn new<M>(condition: impl Condition<M>) -> Self {
+        Self {
+            system: Arc::new(Mutex::new(ConditionSystem {
+                system: Box::new(IntoSystem::into_system(condition)),
+                initialized: false,
+            })),
+            evaluated: Arc::new(AtomicBool::new(false)),
+            holds: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether the condition held in the main world frame that was extracted last, the main world
+    /// may already be evaluating the next frame while the render world dispatches this one
+    pub fn holds(&self) -> bool {
+        self.holds.load(Ordering::Acquire)
+    }
+
+    fn evaluate(&self, world: &mut World) {
+        let mut condition = self.system.lock().expect("Run condition poisoned");
+        if !condition.initialized {
+            condition.system.initialize(world);
+            condition.initialized = true;
+        }
+        let holds = condition.system.run((), world);
+        self.evaluated.store(holds, Ordering::Release);
+    }
+
+    fn extract(&self) {
+        self.holds
+            .store(self.evaluated.load(Ordering::Acquire), Ordering::Release);
+    }
+}
+
+impl fmt::Debug for RunCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunCondition")
+            .field("holds", &self.holds())
+            .finish()
+    }
+}
+
+/// The run conditions of every update entry of a plugin
+#[derive(Resource)]
+pub(super) struct DispatchConditions<EntryTy> {
+    pub conditions: Vec<RunCondition>,
+    _phantom: PhantomData<EntryTy>,
+}
+
+impl<T> DispatchConditions<T> {
+    pub(super) fn new(conditions: Vec<RunCondition>) -> Self {
+        Self {
+            conditions,
+            _phantom: Default::default(),
+        }
+    }
+}
+
+pub(super) fn evaluate_conditions<EntryTy: Send + Sync + 'static>(world: &mut World) {
+    world.resource_scope(|world, conditions: Mut<DispatchConditions<EntryTy>>| {
+        for condition in &conditions.conditions {
+            condition.evaluate(world);
+        }
+    });
+}
+
+pub(super) fn extract_conditions<EntryTy: Send + Sync + 'static>(
+    conditions: Extract<Option<Res<DispatchConditions<EntryTy>>>>,
+) {
+    for condition in conditions.iter().flat_map(|c| &c.conditions) {
+        condition.extract();
+    }
+}
@@ -0,0 +1,54 @@
+use std::borrow::Cow;
+
+use bevy_asset::{AssetPath, AssetServer, Assets, Handle};
+use bevy_ecs::world::World;
+use bevy_render::render_resource::Shader;
+
+/// Where a shader plugin loads its WGSL from
+#[derive(Clone, Debug)]
+pub enum ShaderSource {
+    /// An asset path such as `shaders/life.wgsl`, including `embedded://` paths from `embedded_asset!`
+    Path(AssetPath<'static>),
+    /// An already loaded or created shader
+    Handle(Handle<Shader>),
+    /// WGSL source code, such as from `include_str!`
+    Wgsl(Cow<'static, str>),
+}
+
+impl ShaderSource {
+    pub fn wgsl(source: impl Into<Cow<'static, str>>) -> Self {
+        Self::Wgsl(source.into())
+    }
+
+    /// Resolves the source into a shader handle, `label` names embedded shaders in errors
+    pub(super) fn load(self, world: &mut World, label: &str) -> Handle<Shader> {
+        match self {
+            ShaderSource::Path(path) => world.resource::<AssetServer>().load(path),
+            ShaderSource::Handle(handle) => handle,
+            ShaderSource::Wgsl(source) => world
+                .resource_mut::<Assets<Shader>>()
+                .add(Shader::from_wgsl(source, label)),
+        }
+    }
+}
+
+impl From<&'static str> for ShaderSource {
+    fn from(path: &'static str) -> Self {
+        Self::Path(path.into())
+    }
+}
+impl From<String> for ShaderSource {
+    fn from(path: String) -> Self {
+        Self::Path(path.into())
+    }
+}
+impl From<AssetPath<'static>> for ShaderSource {
+    fn from(path: AssetPath<'static>) -> Self {
+        Self::Path(path)
+    }
+}
+impl From<Handle<Shader>> for ShaderSource {
+    fn from(handle: Handle<Shader>) -> Self {
+        Self::Handle(handle)
+    }
+}
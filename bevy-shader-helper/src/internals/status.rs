@@ -0,0 +1,90 @@
+use std::fmt;
+
+use bevy_ecs::{
+    event::Event,
+    system::{ResMut, Resource},
+};
+use bevy_render::MainWorld;
+
+/// Whether the shader of a plugin can be dispatched, mirrored from the render world
+/// ```ignore
+/// fn show_errors(status: Res<ShaderStatus<HelloEntries>>) {
+///     if let ShaderStatus::Failed(errors) = status.as_ref() {
+///         errors.iter().for_each(|e| error!("{e}"));
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub enum ShaderStatus<EntryTy> {
+    #[default]
+    Loading,
+    /// The shader changed and is recompiling, see [`HotReload`](super::compute::HotReload)
+    Reloading,
+    Ready,
+    /// Nothing new is dispatched until every error is resolved, previous pipelines may keep running
+    Failed(Vec<ShaderError<EntryTy>>),
+}
+
+/// Sent once for every new error of a plugin
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct ShaderError<EntryTy> {
+    /// The entry whose pipeline failed to compile, `None` when the plugin itself is misconfigured
+    pub entry: Option<EntryTy>,
+    /// The shader diagnostic, or what is missing from the plugin
+    pub message: String,
+}
+
+impl<T: fmt::Debug> fmt::Display for ShaderError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.entry {
+            Some(entry) => write!(f, "Failed to load shader entry {entry:?}: {}", self.message),
+            None => write!(f, "Failed to set up shader: {}", self.message),
+        }
+    }
+}
+
+/// Written by the compute node, and moved to the main world during extraction
+#[derive(Resource)]
+pub(super) struct ShaderReport<EntryTy> {
+    pub status: ShaderStatus<EntryTy>,
+    pub changed: bool,
+}
+
+impl<T> Default for ShaderReport<T> {
+    fn default() -> Self {
+        Self {
+            status: ShaderStatus::Loading,
+            changed: false,
+        }
+    }
+}
+
+impl<EntryTy: PartialEq + Clone> ShaderReport<EntryTy> {
+    pub(super) fn update(&mut self, status: ShaderStatus<EntryTy>) {
+        if self.status != status {
+            self.status = status;
+            self.changed = true;
+        }
+    }
+}
+
+pub(super) fn extract_status<EntryTy: Clone + PartialEq + Send + Sync + 'static>(
+    mut main_world: ResMut<MainWorld>,
+    mut report: ResMut<ShaderReport<EntryTy>>,
+) {
+    if !report.changed {
+        return;
+    }
+    report.changed = false;
+
+    let errors = match &report.status {
+        ShaderStatus::Failed(errors) => errors.clone(),
+        _ => vec![],
+    };
+    let reported = match main_world.get_resource::<ShaderStatus<EntryTy>>() {
+        Some(ShaderStatus::Failed(reported)) => reported.clone(),
+        _ => vec![],
+    };
+    main_world.insert_resource(report.status.clone());
+    main_world.send_event_batch(errors.into_iter().filter(|e| !reported.contains(e)));
+}
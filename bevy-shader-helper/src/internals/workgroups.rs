@@ -0,0 +1,395 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_ecs::{
+    event::EventReader,
+    system::{Res, ResMut, Resource},
+    world::World,
+};
+use bevy_math::UVec3;
+use bevy_render::{
+    Extract,
+    render_asset::RenderAssets,
+    render_resource::{
+        Buffer, BufferDescriptor, BufferUsages, Shader, ShaderDefVal, ShaderImport, ShaderStages,
+        Source,
+    },
+    renderer::RenderDevice,
+    storage::GpuShaderStorageBuffer,
+    texture::GpuImage,
+};
+use naga_oil::compose::{Composer, NagaModuleDescriptor, ShaderDefValue};
+use tracing::warn;
+
+use super::{
+    binding::ShaderDataDetails,
+    buffers::{BindingExtent, BufferGroup, ResourceId},
+};
+
+/// How many workgroups an entry dispatches
+///
+/// [`Workgroups::Invocations`] and [`Workgroups::Binding`] divide by the `@workgroup_size` of the entry,
+/// which is read from the shader after its `#import`s and `#ifdef`s are processed
+/// ```ignore
+/// // One invocation for every element of `a`, even after it was resized
+/// Entry::new(HelloEntries::Update, Workgroups::Binding { group: 0, binding: 0 })
+/// // As many workgroups as `cull` wrote into the `#[indirect]` buffer at binding 3
+/// Entry::new(HelloEntries::Shade, Workgroups::Indirect { group: 0, binding: 3, offset: 0 })
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workgroups {
+    Fixed(u32, u32, u32),
+    /// Enough workgroups to run at least this many invocations
+    Invocations(u32, u32, u32),
+    /// One invocation for every element of the buffer, or texel of the texture, bound at `group` and
+    /// `binding`
+    Binding {
+        group: u32,
+        binding: u32,
+    },
+    /// Reads the workgroup counts from three `u32`s at `offset` bytes into the `#[indirect]` buffer bound
+    /// at `group` and `binding`, as written by the entries dispatched before. The offset is a
+    /// multiple of 4, and the counts fit in the buffer
+    Indirect {
+        group: u32,
+        binding: u32,
+        offset: u64,
+    },
+}
+
+/// The three `u32` workgroup counts of a [`Workgroups::Indirect`] dispatch
+pub(super) const INDIRECT_ARGUMENTS_SIZE: u64 = 12;
+
+impl From<(u32, u32, u32)> for Workgroups {
+    fn from(value: (u32, u32, u32)) -> Self {
+        Self::Fixed(value.0, value.1, value.2)
+    }
+}
+
+impl Workgroups {
+    /// `None` while the workgroup size or the bound resource is not known yet, or the indirect
+    /// arguments no longer fit in their buffer
+    pub(super) fn resolve<'a, PipelineTy, EntryTy>(
+        &self,
+        key: usize,
+        sources: &WorkgroupSources<'a, PipelineTy, EntryTy>,
+    ) -> Option<ResolvedWorkgroups<'a>> {
+        let invocations = match *self {
+            Workgroups::Fixed(x, y, z) => return Some(ResolvedWorkgroups::Direct(x, y, z)),
+            Workgroups::Invocations(x, y, z) => UVec3::new(x, y, z),
+            Workgroups::Binding { group, binding } => sources.extents.get(group, binding)?,
+            Workgroups::Indirect {
+                group,
+                binding,
+                offset,
+            } => {
+                let (bound, arguments) = sources.indirect.buffers.get(&(group, binding))?;
+                // Only checked against the initial data when the plugin is built, the buffer may
+                // have been resized since
+                if offset.saturating_add(INDIRECT_ARGUMENTS_SIZE) > bound.size() {
+                    return None;
+                }
+                return Some(ResolvedWorkgroups::Indirect {
+                    bound,
+                    arguments,
+                    offset,
+                });
+            }
+        };
+        let size = sources.workgroup_sizes.get(key)?.max(UVec3::ONE);
+        Some(ResolvedWorkgroups::Direct(
+            invocations.x.div_ceil(size.x),
+            invocations.y.div_ceil(size.y),
+            invocations.z.div_ceil(size.z),
+        ))
+    }
+}
+
+pub(super) enum ResolvedWorkgroups<'a> {
+    Direct(u32, u32, u32),
+    /// The arguments are copied out of the bound buffer first, as a buffer cannot be used as indirect
+    /// arguments while it is bound for writing
+    Indirect {
+        bound: &'a Buffer,
+        arguments: &'a Buffer,
+        offset: u64,
+    },
+}
+
+/// Everything dynamic workgroup counts are resolved from
+pub(super) struct WorkgroupSources<'a, PipelineTy, EntryTy> {
+    pub workgroup_sizes: &'a WorkgroupSizes<EntryTy>,
+    pub extents: &'a BindingExtents<PipelineTy>,
+    pub indirect: &'a IndirectBuffers<PipelineTy>,
+}
+
+impl<'a, PipelineTy: Send + Sync + 'static, EntryTy: Send + Sync + 'static>
+    WorkgroupSources<'a, PipelineTy, EntryTy>
+{
+    pub(super) fn new(world: &'a World) -> Self {
+        Self {
+            workgroup_sizes: world.resource(),
+            extents: world.resource(),
+            indirect: world.resource(),
+        }
+    }
+}
+
+/// The `@workgroup_size` of every entry, keyed by [`ShaderEntry::as_key`](super::entries::ShaderEntry::as_key)
+#[derive(Resource)]
+pub(super) struct WorkgroupSizes<EntryTy> {
+    sizes: Vec<Option<UVec3>>,
+    _phantom: PhantomData<fn() -> EntryTy>,
+}
+
+impl<T> Default for WorkgroupSizes<T> {
+    fn default() -> Self {
+        Self {
+            sizes: vec![],
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> WorkgroupSizes<T> {
+    pub(super) fn get(&self, key: usize) -> Option<UVec3> {
+        self.sizes.get(key).copied().flatten()
+    }
+}
+
+/// The shader the workgroup sizes of a plugin are read from
+#[derive(Resource)]
+pub(super) struct ReflectedShader<EntryTy> {
+    shader: Handle<Shader>,
+    /// The entry point name of every entry, keyed by [`ShaderEntry::as_key`](super::entries::ShaderEntry::as_key)
+    names: Vec<&'static str>,
+    _phantom: PhantomData<fn() -> EntryTy>,
+}
+
+impl<T> ReflectedShader<T> {
+    pub(super) fn new(shader: Handle<Shader>, names: &[&'static str]) -> Self {
+        Self {
+            shader,
+            names: names.to_vec(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Reads the workgroup sizes again whenever a shader is loaded or changed, which includes the
+/// shaders it imports
+pub(super) fn extract_workgroup_sizes<EntryTy: Send + Sync + 'static>(
+    mut events: Extract<EventReader<AssetEvent<Shader>>>,
+    shaders: Extract<Res<Assets<Shader>>>,
+    render_device: Res<RenderDevice>,
+    reflected: Res<ReflectedShader<EntryTy>>,
+    mut sizes: ResMut<WorkgroupSizes<EntryTy>>,
+) {
+    // Every event is read, so none of them is seen again next frame
+    let changes = events.read().filter(|event| {
+        matches!(
+            event,
+            AssetEvent::Added { .. }
+                | AssetEvent::Modified { .. }
+                | AssetEvent::LoadedWithDependencies { .. }
+        )
+    });
+    let changed = changes.count() > 0;
+    let Some(shader) = shaders.get(&reflected.shader).filter(|_| changed) else {
+        return;
+    };
+    match reflect(shader, &shaders, &render_device, &reflected.names) {
+        Ok(Some(reflected)) => sizes.sizes = reflected,
+        // Read once the missing import is loaded
+        Ok(None) => {}
+        Err(e) => warn!("Cannot read the workgroup sizes of {}: {e}", shader.path),
+    }
+}
+
+/// Processes the shader like the pipeline cache does, `None` while one of its imports is not loaded
+fn reflect(
+    shader: &Shader,
+    shaders: &Assets<Shader>,
+    render_device: &RenderDevice,
+    names: &[&str],
+) -> Result<Option<Vec<Option<UVec3>>>, String> {
+    if matches!(shader.source, Source::SpirV(_)) {
+        return Err("SPIR-V sources are not supported".to_string());
+    }
+    let imports: HashMap<_, _> = shaders
+        .iter()
+        .map(|(_, shader)| (shader.import_path(), shader))
+        .collect();
+    // The pipeline cache validates the shader and reports its errors
+    let mut composer = Composer::non_validating();
+    for import in shader.imports() {
+        if !add_import(&mut composer, &imports, import)? {
+            return Ok(None);
+        }
+    }
+    // The pipelines have no shader defs of their own, the pipeline cache adds this one to every shader
+    let limit = ShaderDefVal::UInt(
+        "AVAILABLE_STORAGE_BUFFER_BINDINGS".to_string(),
+        render_device.limits().max_storage_buffers_per_shader_stage,
+    );
+    let shader_defs = [limit]
+        .into_iter()
+        .chain(shader.shader_defs.iter().cloned())
+        .map(|def| match def {
+            ShaderDefVal::Bool(name, value) => (name, ShaderDefValue::Bool(value)),
+            ShaderDefVal::Int(name, value) => (name, ShaderDefValue::Int(value)),
+            ShaderDefVal::UInt(name, value) => (name, ShaderDefValue::UInt(value)),
+        })
+        .collect();
+    let module = composer
+        .make_naga_module(NagaModuleDescriptor {
+            shader_defs,
+            ..shader.into()
+        })
+        .map_err(|e| e.emit_to_string(&composer))?;
+    let sizes = names.iter().map(|name| {
+        module
+            .entry_points
+            .iter()
+            .find(|entry| entry.name == *name)
+            .map(|entry| UVec3::from_array(entry.workgroup_size))
+    });
+
+    Ok(Some(sizes.collect()))
+}
+
+/// Adds an import after the imports it depends on, `false` when one of them is not loaded
+fn add_import(
+    composer: &mut Composer,
+    imports: &HashMap<&ShaderImport, &Shader>,
+    import: &ShaderImport,
+) -> Result<bool, String> {
+    if composer.contains_module(&import.module_name()) {
+        return Ok(true);
+    }
+    let Some(shader) = imports.get(import) else {
+        return Ok(false);
+    };
+    for import in shader.imports() {
+        if !add_import(composer, imports, import)? {
+            return Ok(false);
+        }
+    }
+    composer
+        .add_composable_module((*shader).into())
+        .map(|_| ())
+        .map_err(|e| e.emit_to_string(composer))?;
+
+    Ok(true)
+}
+
+/// The size of every bound resource in elements or texels, keyed by group and binding
+#[derive(Resource)]
+pub(super) struct BindingExtents<T> {
+    extents: HashMap<(u32, u32), UVec3>,
+    /// The resources the extents were measured from
+    resources: Vec<Option<ResourceId>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for BindingExtents<T> {
+    fn default() -> Self {
+        Self {
+            extents: HashMap::new(),
+            resources: vec![],
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> BindingExtents<T> {
+    fn get(&self, group: u32, binding: u32) -> Option<UVec3> {
+        self.extents.get(&(group, binding)).copied()
+    }
+}
+
+/// Measured again whenever a bound resource is replaced, which resizing a buffer or image does, so
+/// the workgroup counts follow it
+pub(super) fn prepare_extents<
+    const B: usize,
+    const E: usize,
+    DataTy: Clone + ShaderDataDetails<B, E>,
+    PipelineTy: Send + Sync + 'static,
+    BuffersTy: Resource + BufferGroup<DataTy, B>,
+>(
+    buffer: Res<BuffersTy>,
+    buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
+    images: Res<RenderAssets<GpuImage>>,
+    mut extents: ResMut<BindingExtents<PipelineTy>>,
+) {
+    let resources = buffer.resource_ids(&buffers, &images);
+    if resources == extents.resources {
+        return;
+    }
+    extents.resources = resources;
+    let layout = DataTy::buffer_entries(ShaderStages::COMPUTE);
+    let bindings = DataTy::groups()
+        .into_iter()
+        .zip(layout.iter().map(|entry| entry.binding))
+        .zip(DataTy::element_sizes())
+        .zip(buffer.extents(&buffers, &images));
+    extents.extents = bindings
+        .filter_map(|((index, element_size), extent)| {
+            let extent = match extent? {
+                BindingExtent::Buffer(len) => UVec3::new((len / element_size.max(1)) as u32, 1, 1),
+                BindingExtent::Texture(size) => size,
+            };
+            Some((index, extent))
+        })
+        .collect();
+}
+
+/// The argument buffers of indirect dispatches, and the `#[indirect]` buffers they are copied from
+#[derive(Resource)]
+pub(super) struct IndirectBuffers<T> {
+    buffers: HashMap<(u32, u32), (Buffer, Buffer)>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for IndirectBuffers<T> {
+    fn default() -> Self {
+        Self {
+            buffers: HashMap::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Creates a new argument buffer whenever an `#[indirect]` buffer was replaced or resized
+pub(super) fn prepare_indirect_buffers<
+    const B: usize,
+    DataTy: Clone,
+    PipelineTy: Send + Sync + 'static,
+    BuffersTy: Resource + BufferGroup<DataTy, B>,
+>(
+    render_device: Res<RenderDevice>,
+    buffer: Res<BuffersTy>,
+    buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
+    mut indirect: ResMut<IndirectBuffers<PipelineTy>>,
+) {
+    let bound = BuffersTy::indirect_bindings()
+        .into_iter()
+        .zip(buffer.indirect_buffers(&buffers));
+    for (index, source) in bound {
+        let Some(source) = source else {
+            indirect.buffers.remove(&index);
+            continue;
+        };
+        let current = indirect.buffers.get(&index);
+        if current.is_some_and(|(bound, _)| bound.id() == source.id()) {
+            continue;
+        }
+        let arguments = render_device.create_buffer(&BufferDescriptor {
+            label: Some("indirect_arguments"),
+            size: source.size(),
+            usage: BufferUsages::INDIRECT | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        indirect.buffers.insert(index, (source, arguments));
+    }
+}
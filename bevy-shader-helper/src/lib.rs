@@ -7,7 +7,16 @@ pub use crate::builders::*;
 pub mod prelude {
     pub use crate::{
         BuildableShader, ImageBuilder, ImageData,
-        internals::buffers::{ReadableBuffer, WriteableBuffer},
+        internals::buffers::{PingPongFront, ReadableBuffer, WriteError, WriteableBuffer},
+        internals::compute::HotReload,
+        internals::control::ShaderControl,
+        internals::entries::{DispatchShader, Entry},
+        internals::interpreter::CpuExecutor,
+        internals::runner::{ComputeResults, ComputeRunner, RunnerError},
+        internals::schedule::DispatchSchedule,
+        internals::source::ShaderSource,
+        internals::status::{ShaderError, ShaderStatus},
+        internals::workgroups::Workgroups,
     };
 }
 
@@ -20,4 +29,5 @@ pub mod bevy {
     pub use bevy_image::Image;
     pub use bevy_ecs::prelude::{Commands, Resource};
     pub use bevy_ecs;
+    pub use bevy_math as math;
 }
\ No newline at end of file
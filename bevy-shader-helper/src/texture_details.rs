@@ -1,28 +1,148 @@
-use bevy_render::render_resource::{TextureDimension, TextureFormat};
+use bevy_render::render_resource::{TextureDimension, TextureFormat, TextureViewDimension};
+use bytemuck::Pod;
 
-// TODO: macro
 pub trait ToTextureDimension {
     fn texture_dimension() -> TextureDimension;
+    /// How the shader views the texture, arrays keep their layers in `depth_or_array_layers`
+    fn view_dimension() -> TextureViewDimension;
 }
 
-pub struct D1;
-pub struct D2;
-impl ToTextureDimension for D2 {
-    fn texture_dimension() -> TextureDimension {
-        TextureDimension::D2
-    }
+macro_rules! texture_dimensions {
+    ($($view:ident => $dimension:ident),* $(,)?) => {$(
+        pub struct $view;
+
+        impl ToTextureDimension for $view {
+            fn texture_dimension() -> TextureDimension {
+                TextureDimension::$dimension
+            }
+
+            fn view_dimension() -> TextureViewDimension {
+                TextureViewDimension::$view
+            }
+        }
+    )*};
+}
+
+texture_dimensions! {
+    D1 => D1,
+    D2 => D2,
+    D2Array => D2,
+    D3 => D3,
 }
-pub struct D3;
 
-// TODO macro
 pub trait ToTextureFormat {
+    /// The data of one texel, as [`ImageBuilder::pixels`](crate::ImageBuilder::pixels) takes it
+    type Pixel: Pod;
     fn texture_format() -> TextureFormat;
 }
 
-pub struct R32Float;
+/// Formats a `ReadOnly` storage texture can have, `Bgra8Unorm` can only be written
+/// ```compile_fail,E0277
+/// use bevy_shader_helper::{ImageBuilder, internals::prelude::*, texture_details::{Bgra8Unorm, D2}};
+///
+/// #[derive(ShaderDataDetails, Clone)]
+/// #[entry("main")]
+/// struct Data {
+///     #[texture(ReadOnly, Bgra8Unorm, D2)]
+///     texels: ImageBuilder<Bgra8Unorm, D2>,
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` storage textures cannot be read",
+    label = "use WriteOnly access for this format"
+)]
+pub trait ReadableStorage: ToTextureFormat {}
+
+/// Formats a `ReadWrite` storage texture can have, reading and writing the same texture needs one
+/// 32 bit channel
+/// ```compile_fail,E0277
+/// use bevy_shader_helper::{ImageBuilder, internals::prelude::*, texture_details::{D2, Rgba8Unorm}};
+///
+/// #[derive(ShaderDataDetails, Clone)]
+/// #[entry("main")]
+/// struct Data {
+///     #[texture(ReadWrite, Rgba8Unorm, D2)]
+///     texels: ImageBuilder<Rgba8Unorm, D2>,
+/// }
+/// ```
+/// ```
+/// use bevy_shader_helper::{ImageBuilder, internals::prelude::*, texture_details::{D2, R32Float}};
+///
+/// #[derive(ShaderDataDetails, Clone)]
+/// #[entry("main")]
+/// struct Data {
+///     #[texture(ReadWrite, R32Float, D2)]
+///     texels: ImageBuilder<R32Float, D2>,
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` storage textures cannot be read and written by the same binding",
+    label = "use ReadOnly or WriteOnly access, or an R32Uint, R32Sint or R32Float format"
+)]
+pub trait ReadWriteStorage: ReadableStorage {}
+
+macro_rules! texture_formats {
+    ($($format:ident => $pixel:ty $(: $access:ident $(+ $more:ident)*)?),* $(,)?) => {$(
+        pub struct $format;
 
-impl ToTextureFormat for R32Float {
-    fn texture_format() -> TextureFormat {
-        TextureFormat::R32Float
-    }
-}
\ No newline at end of file
+        impl ToTextureFormat for $format {
+            type Pixel = $pixel;
+
+            fn texture_format() -> TextureFormat {
+                TextureFormat::$format
+            }
+        }
+
+        $(
+            impl $access for $format {}
+            $(impl $more for $format {})*
+        )?
+    )*};
+}
+
+// Every format a storage texture can have, half floats are kept as their bits and packed formats
+// as the whole texel. The derives resolve `#[texture(..)]` formats and their readbacks through
+// these markers, so this is the only list of storage formats. The accesses after the pixel type
+// are the ones the derives accept besides WriteOnly
+texture_formats! {
+    R8Unorm => u8: ReadableStorage,
+    R8Snorm => i8: ReadableStorage,
+    R8Uint => u8: ReadableStorage,
+    R8Sint => i8: ReadableStorage,
+    R16Uint => u16: ReadableStorage,
+    R16Sint => i16: ReadableStorage,
+    R16Float => u16: ReadableStorage,
+    R16Unorm => u16: ReadableStorage,
+    R16Snorm => i16: ReadableStorage,
+    Rg8Unorm => [u8; 2]: ReadableStorage,
+    Rg8Snorm => [i8; 2]: ReadableStorage,
+    Rg8Uint => [u8; 2]: ReadableStorage,
+    Rg8Sint => [i8; 2]: ReadableStorage,
+    R32Uint => u32: ReadableStorage + ReadWriteStorage,
+    R32Sint => i32: ReadableStorage + ReadWriteStorage,
+    R32Float => f32: ReadableStorage + ReadWriteStorage,
+    Rg16Uint => [u16; 2]: ReadableStorage,
+    Rg16Sint => [i16; 2]: ReadableStorage,
+    Rg16Float => [u16; 2]: ReadableStorage,
+    Rg16Unorm => [u16; 2]: ReadableStorage,
+    Rg16Snorm => [i16; 2]: ReadableStorage,
+    Rgba8Unorm => [u8; 4]: ReadableStorage,
+    Rgba8Snorm => [i8; 4]: ReadableStorage,
+    Rgba8Uint => [u8; 4]: ReadableStorage,
+    Rgba8Sint => [i8; 4]: ReadableStorage,
+    Bgra8Unorm => [u8; 4],
+    Rgb10a2Uint => u32: ReadableStorage,
+    Rgb10a2Unorm => u32: ReadableStorage,
+    Rg11b10Ufloat => u32: ReadableStorage,
+    Rg32Uint => [u32; 2]: ReadableStorage,
+    Rg32Sint => [i32; 2]: ReadableStorage,
+    Rg32Float => [f32; 2]: ReadableStorage,
+    Rgba16Uint => [u16; 4]: ReadableStorage,
+    Rgba16Sint => [i16; 4]: ReadableStorage,
+    Rgba16Float => [u16; 4]: ReadableStorage,
+    Rgba16Unorm => [u16; 4]: ReadableStorage,
+    Rgba16Snorm => [i16; 4]: ReadableStorage,
+    Rgba32Uint => [u32; 4]: ReadableStorage,
+    Rgba32Sint => [i32; 4]: ReadableStorage,
+    Rgba32Float => [f32; 4]: ReadableStorage,
+}
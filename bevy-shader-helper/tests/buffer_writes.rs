@@ -0,0 +1,149 @@
+use std::sync::{Arc, Mutex};
+
+use bevy_shader_helper::{
+    ImageBuilder, ShaderBuilder,
+    bevy::{
+        Assets, Handle, Image, Resource,
+        math::Vec3,
+        render::{
+            render_asset::RenderAssetUsages, render_resource::Extent3d,
+            storage::ShaderStorageBuffer,
+        },
+    },
+    internals::prelude::*,
+    texture_details::{D2, R32Float},
+};
+
+mod common;
+
+#[test]
+fn test_write_range_after_header() {
+    let mut buffers = Assets::<ShaderStorageBuffer>::default();
+    // A u32 header, followed by a runtime sized array of three vec3<f32>
+    let particles = ShaderStorageBuffer::new(&[0; 64], RenderAssetUsages::default());
+    let buffer: ReadBuffer<_> = buffers.add(particles).into();
+
+    // The array follows the header at its 16 byte alignment, and every vec3 is padded to 16 bytes
+    buffer
+        .write_field_range(&mut buffers, 16, 1, &[Vec3::new(1., 2., 3.)])
+        .unwrap();
+
+    let data = buffers.get(&buffer.handle).unwrap().data.as_ref().unwrap();
+    let floats: &[f32] = bytemuck::cast_slice(&data[32..44]);
+    assert_eq!(floats, [1., 2., 3.]);
+    assert!(data[16..32].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn test_write_range_out_of_bounds() {
+    let mut buffers = Assets::<ShaderStorageBuffer>::default();
+    let buffer: ReadWriteBuffer<_> = buffers.add(ShaderStorageBuffer::from(vec![0u32; 4])).into();
+
+    assert_eq!(
+        buffer.write_range(&mut buffers, 3, &[1u32, 2]),
+        Err(WriteError::OutOfRange {
+            start: 12,
+            end: 20,
+            len: 16
+        })
+    );
+
+    let missing = ReadWriteBuffer::<ShaderStorageBuffer>::from(Handle::default());
+    assert_eq!(
+        missing.write_range(&mut buffers, 0, &[1u32]),
+        Err(WriteError::MissingAsset)
+    );
+}
+
+const COPY: &str = "
+@group(0) @binding(0) var input: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(1) fn copy(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = textureLoad(input, vec2(id.x, 0u)).x;
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum CopyEntries {
+    Copy,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("copy")]
+struct CopyData {
+    #[texture(ReadOnly, R32Float, D2)]
+    input: ImageBuilder<R32Float, D2>,
+    output: Vec<f32>,
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(CopyData)]
+struct CopyBuffers {
+    #[texture]
+    input: ReadBuffer<Image>,
+    #[writeable]
+    output: ReadWriteBuffer<ShaderStorageBuffer>,
+}
+
+type CopyShaderPlugin = ShaderPlugin<CopyData, CopyEntries, CopyBuffers, 2, 1>;
+
+#[test]
+fn test_write_texels_after_extract() {
+    let size = Extent3d {
+        width: 2,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let plugin: CopyShaderPlugin = ShaderBuilder::default()
+        .initial_data(CopyData {
+            input: size.into(),
+            output: vec![0.; 2],
+        })
+        .shader(ShaderSource::wgsl(COPY))
+        .on_update([Entry::new(CopyEntries::Copy, (2, 1, 1))])
+        .build();
+    let Some(mut app) = common::headless_app(plugin) else {
+        return eprintln!("Skipped, no GPU or software adapter");
+    };
+
+    // The render world extracted the image by the time the shader runs
+    common::update_until(&mut app, |world| {
+        matches!(
+            world.get_resource::<ShaderStatus<CopyEntries>>(),
+            Some(ShaderStatus::Ready)
+        )
+    });
+    app.update();
+
+    let world = app.world_mut();
+    let buffers = world.resource::<CopyBuffers>().clone();
+    let mut images = world.resource_mut::<Assets<Image>>();
+    // R32Float texels take 4 bytes, not the 16 of a vec4
+    assert_eq!(
+        buffers.input.set(&mut images, &[[0f32; 4]; 2]),
+        Err(WriteError::TexelSize {
+            expected: 4,
+            actual: 16
+        })
+    );
+    assert_eq!(
+        buffers.input.set(&mut images, &[1f32]),
+        Err(WriteError::OutOfRange {
+            start: 0,
+            end: 4,
+            len: 8
+        })
+    );
+    buffers.input.set(&mut images, &[1f32, 0.]).unwrap();
+    buffers.input.write_range(&mut images, 1, &[2f32]).unwrap();
+
+    let output = Arc::new(Mutex::new(None));
+    let read = output.clone();
+    world.spawn(buffers.readback_output(move |data| {
+        read.lock().unwrap().get_or_insert(data);
+    }));
+    common::update_until(&mut app, |_| output.lock().unwrap().is_some());
+
+    assert_eq!(output.lock().unwrap().take(), Some(vec![1., 2.]));
+}
@@ -0,0 +1,54 @@
+//! A headless app for tests which drive its frames themselves, where the `ComputeRunner` would
+//! hide the app
+
+use bevy_app::{App, Plugin, PluginsState};
+use bevy_asset::AssetPlugin;
+use bevy_core::{FrameCountPlugin, TaskPoolPlugin};
+use bevy_ecs::world::World;
+use bevy_render::{RenderPlugin, texture::ImagePlugin};
+use bevy_tasks::{block_on, tick_global_task_pools_on_main_thread};
+use bevy_time::TimePlugin;
+use bevy_window::{ExitCondition, WindowPlugin};
+use wgpu::{Instance, RequestAdapterOptions};
+
+/// `None` when the render plugin would not find an adapter
+pub fn headless_app(plugin: impl Plugin) -> Option<App> {
+    let instance = Instance::default();
+    block_on(instance.request_adapter(&RequestAdapterOptions::default()))?;
+
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        FrameCountPlugin,
+        TimePlugin,
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        AssetPlugin::default(),
+        RenderPlugin {
+            synchronous_pipeline_compilation: true,
+            ..Default::default()
+        },
+        ImagePlugin::default(),
+        plugin,
+    ));
+    while app.plugins_state() == PluginsState::Adding {
+        tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    Some(app)
+}
+
+pub fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) {
+    for _ in 0..1000 {
+        app.update();
+        if done(app.world_mut()) {
+            return;
+        }
+    }
+    panic!("Not done after 1000 frames");
+}
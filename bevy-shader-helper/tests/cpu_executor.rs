@@ -0,0 +1,287 @@
+// The size checks `ShaderType` derives are never called
+#![allow(dead_code)]
+
+use bevy_shader_helper::{
+    ImageBuilder, ShaderBuilder,
+    bevy::{
+        Image, Resource,
+        render::{render_resource::Extent3d, storage::ShaderStorageBuffer},
+    },
+    internals::prelude::*,
+};
+
+const HELLO: &str = "
+struct Foo {
+    bar: u32,
+    bazz: f32,
+}
+
+@group(0) @binding(0) var<storage, read_write> a: array<u32>;
+@group(0) @binding(1) var<storage, read>       b: Foo;
+@group(0) @binding(2) var<storage, read>       c: vec3<f32>;
+@group(0) @binding(3) var                      d: texture_storage_2d<r32float, read_write>;
+
+@compute @workgroup_size(1) fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    a[global_id.x] = b.bar;
+    let loc = vec2<u32>(global_id.x, global_id.y);
+    textureStore(d, loc, vec4<f32>(b.bazz, c.x, c.y, c.z));
+}
+
+@compute @workgroup_size(1) fn update(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    a[global_id.x] += 1u;
+    let loc = vec2<u32>(global_id.x, global_id.y);
+    let x = textureLoad(d, loc);
+    textureStore(d, loc, x + 1.);
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum HelloEntries {
+    Main,
+    Update,
+}
+
+#[derive(ShaderType, Clone)]
+struct Foo {
+    bar: u32,
+    bazz: f32,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("main")]
+#[entry("update")]
+struct HelloData {
+    a: Vec<u32>,
+    #[read_only]
+    b: Foo,
+    #[read_only]
+    c: Vec3,
+    #[texture(ReadWrite, R32Float, D2)]
+    d: ImageBuilder<R32Float, D2>,
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(HelloData)]
+struct HelloBuffers {
+    #[writeable]
+    a: ReadWriteBuffer<ShaderStorageBuffer>,
+    b: ReadBuffer<ShaderStorageBuffer>,
+    c: ReadBuffer<ShaderStorageBuffer>,
+    #[writeable]
+    #[texture]
+    d: ReadWriteBuffer<Image>,
+}
+
+type HelloShaderPlugin = ShaderPlugin<HelloData, HelloEntries, HelloBuffers, 4, 2>;
+
+fn hello_plugin() -> HelloShaderPlugin {
+    hello_plugin_with(HELLO)
+}
+
+fn hello_plugin_with(shader: impl Into<String>) -> HelloShaderPlugin {
+    ShaderBuilder::default()
+        .initial_data(HelloData {
+            a: vec![1, 2, 3],
+            b: Foo { bar: 1, bazz: 2. },
+            c: vec3(1., 2., 3.),
+            d: Extent3d {
+                width: 3,
+                height: 1,
+                depth_or_array_layers: 1,
+            }
+            .into(),
+        })
+        .shader(ShaderSource::wgsl(shader.into()))
+        .build()
+}
+
+#[test]
+fn test_cpu_executor_hello() {
+    let mut executor = CpuExecutor::new(hello_plugin()).unwrap();
+    executor.dispatch(&HelloEntries::Main, (3, 1, 1)).unwrap();
+    for _ in 0..2 {
+        executor
+            .dispatch(
+                &HelloEntries::Update,
+                Workgroups::Binding {
+                    group: 0,
+                    binding: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    assert_eq!(executor.get::<0, 0>(), Some(vec![3, 3, 3]));
+    let texels: Vec<f32> = executor
+        .bytes(0, 3)
+        .unwrap()
+        .chunks_exact(4)
+        .map(|texel| f32::from_le_bytes(texel.try_into().unwrap()))
+        .collect();
+    assert_eq!(texels, [4., 4., 4.]);
+}
+
+// Each `BufferGroup` derive imports `bevy_ecs`, so the second group lives in its own module
+mod language {
+    use super::*;
+
+    const LANGUAGE: &str = "
+    struct Particle {
+        position: vec2<f32>,
+        steps: u32,
+    }
+
+    @group(0) @binding(0) var<storage, read_write> particles: array<Particle>;
+    @group(0) @binding(1) var<storage, read_write> stats: array<atomic<u32>, 4>;
+
+    var<private> calls: u32 = 2u;
+
+    fn collatz(start: u32) -> u32 {
+        var n = start;
+        var steps = 0u;
+        loop {
+            if n <= 1u {
+                break;
+            }
+            n = select(n / 2u, 3u * n + 1u, n % 2u == 1u);
+            steps++;
+        }
+        calls += 1u;
+        return steps;
+    }
+
+    @compute @workgroup_size(4) fn run(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= arrayLength(&particles) {
+            return;
+        }
+        let rotation = mat2x2<f32>(0., 1., -1., 0.);
+        var p = particles[id.x];
+        p.position = rotation * p.position + vec2(f32(id.x));
+        p.steps = collatz(p.steps) + calls;
+        particles[id.x] = p;
+
+        switch id.x % 3u {
+            case 0u: {
+                atomicAdd(&stats[0], 1u);
+            }
+            case 1u, 2u: {
+                atomicMax(&stats[1], id.x);
+            }
+            default: {}
+        }
+        atomicAdd(&stats[2], u32(length(vec2(3., 4.))));
+        for (var i = 0; i < i32(id.x); i++) {
+            atomicAdd(&stats[3], countOneBits(u32(i)) + extractBits(0xF0u, 4u, 2u));
+        }
+    }
+    ";
+
+    #[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+    enum LanguageEntries {
+        Run,
+    }
+
+    #[derive(ShaderType, Clone, Debug, PartialEq)]
+    struct Particle {
+        position: Vec2,
+        steps: u32,
+    }
+
+    #[derive(ShaderDataDetails, Clone)]
+    #[entry("run")]
+    struct LanguageData {
+        particles: Vec<Particle>,
+        stats: [u32; 4],
+    }
+
+    #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+    #[data(LanguageData)]
+    struct LanguageBuffers {
+        #[writeable]
+        particles: ReadWriteBuffer<ShaderStorageBuffer>,
+        #[writeable]
+        stats: ReadWriteBuffer<ShaderStorageBuffer>,
+    }
+
+    #[test]
+    fn test_cpu_executor_language() {
+        let particles = (0..6)
+            .map(|i| Particle {
+                position: vec2(i as f32, 1.),
+                steps: i + 1,
+            })
+            .collect();
+        let plugin: ShaderPlugin<LanguageData, LanguageEntries, LanguageBuffers, 2, 1> =
+            ShaderBuilder::default()
+                .initial_data(LanguageData {
+                    particles,
+                    stats: [0; 4],
+                })
+                .shader(ShaderSource::wgsl(LANGUAGE))
+                .build();
+        let mut executor = CpuExecutor::new(plugin).unwrap();
+        executor
+            .dispatch(&LanguageEntries::Run, Workgroups::Invocations(6, 1, 1))
+            .unwrap();
+
+        let collatz = [0, 1, 7, 2, 5, 8];
+        let expected: Vec<_> = (0..6)
+            .map(|i| Particle {
+                position: vec2(-1. + i as f32, i as f32 * 2.),
+                steps: collatz[i as usize] + 3,
+            })
+            .collect();
+        assert_eq!(executor.get::<0, 0>(), Some(expected));
+        // Invocation n loops over 0..n, adding the one bits of i and 3 extracted bits each time
+        let loops: u32 = (0..6u32)
+            .flat_map(|n| 0..n)
+            .map(|i| i.count_ones() + 3)
+            .sum();
+        assert_eq!(executor.get::<0, 1>(), Some([2, 5, 30, loops]));
+    }
+}
+
+#[test]
+fn test_cpu_executor_clamps_indices() {
+    let mut executor = CpuExecutor::new(hello_plugin()).unwrap();
+    executor.dispatch(&HelloEntries::Update, (4, 1, 1)).unwrap();
+
+    // The fourth invocation updates the last element again, and skips its texel
+    assert_eq!(executor.get::<0, 0>(), Some(vec![2, 3, 5]));
+    let texels: Vec<f32> = executor
+        .bytes(0, 3)
+        .unwrap()
+        .chunks_exact(4)
+        .map(|texel| f32::from_le_bytes(texel.try_into().unwrap()))
+        .collect();
+    assert_eq!(texels, [1., 1., 1.]);
+}
+
+#[test]
+fn test_cpu_executor_errors() {
+    let mut executor = CpuExecutor::new(hello_plugin()).unwrap();
+    let error = executor
+        .dispatch(
+            &HelloEntries::Update,
+            Workgroups::Binding {
+                group: 0,
+                binding: 7,
+            },
+        )
+        .unwrap_err();
+
+    assert_eq!(error.entry, Some(HelloEntries::Update));
+    // A failed dispatch leaves the buffers unchanged
+    assert_eq!(executor.get::<0, 0>(), Some(vec![1, 2, 3]));
+
+    let invalid = HELLO.replace("@workgroup_size(1) fn main", "@workgroup_size(0) fn main");
+    let Err(error) = CpuExecutor::new(hello_plugin_with(invalid)) else {
+        panic!("An invalid shader should not be interpreted");
+    };
+    assert_eq!(error.entry, None);
+    assert!(
+        error.message.contains("Workgroup size"),
+        "{}",
+        error.message
+    );
+}
@@ -0,0 +1,84 @@
+use bevy_app::App;
+use bevy_shader_helper::{
+    ShaderBuilder,
+    bevy::{Resource, render::storage::ShaderStorageBuffer},
+    internals::prelude::*,
+};
+
+const COUNT: &str = "
+@group(0) @binding(0) var<storage, read_write> a: array<u32>;
+@group(0) @binding(1) var<storage, read>       args: array<u32, 4>;
+
+@compute @workgroup_size(1) fn count(@builtin(global_invocation_id) id: vec3<u32>) {
+    a[id.x] += 1u;
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum CountEntries {
+    Count,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("count")]
+struct CountData {
+    a: Vec<u32>,
+    #[read_only]
+    args: [u32; 4],
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(CountData)]
+struct CountBuffers {
+    #[writeable]
+    a: ReadWriteBuffer<ShaderStorageBuffer>,
+    #[indirect]
+    args: ReadBuffer<ShaderStorageBuffer>,
+}
+
+type CountShaderPlugin = ShaderPlugin<CountData, CountEntries, CountBuffers, 2, 1>;
+
+fn count_plugin(offset: u64) -> CountShaderPlugin {
+    ShaderBuilder::default()
+        .initial_data(CountData {
+            a: vec![0; 4],
+            args: [9, 3, 1, 1],
+        })
+        .shader(ShaderSource::wgsl(COUNT))
+        .on_update([Entry::new(
+            CountEntries::Count,
+            Workgroups::Indirect {
+                group: 0,
+                binding: 1,
+                offset,
+            },
+        )])
+        .build()
+}
+
+#[test]
+fn test_indirect_dispatch() {
+    let results = match ComputeRunner::new(count_plugin(4)).run() {
+        Err(RunnerError::NoAdapter) => return eprintln!("Skipped, no GPU or software adapter"),
+        results => results.unwrap(),
+    };
+
+    assert_eq!(results.get::<0, 0>(), Some(vec![1, 1, 1, 0]));
+}
+
+#[test]
+fn test_indirect_offset_errors() {
+    // Reported when the plugin is built, before wgpu would reject the dispatch
+    for (offset, expected) in [(2, "not a multiple of 4"), (8, "do not fit in the 16 bytes")] {
+        let mut app = App::new();
+        app.add_plugins(count_plugin(offset));
+
+        match app.world().resource::<ShaderStatus<CountEntries>>() {
+            ShaderStatus::Failed(errors) => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].message.contains(expected), "{}", errors[0].message);
+            }
+            _ => panic!("Expected a setup error for the offset {offset}"),
+        }
+    }
+}
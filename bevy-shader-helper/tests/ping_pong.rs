@@ -0,0 +1,80 @@
+use bevy_shader_helper::{
+    ShaderBuilder,
+    bevy::{Resource, render::storage::ShaderStorageBuffer},
+    internals::prelude::*,
+};
+use bevy_tasks::block_on;
+use wgpu::{Instance, RequestAdapterOptions};
+
+const COUNT: &str = "
+@group(0) @binding(0) var<storage, read_write> input: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1) fn step(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] + 1u;
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum CountEntries {
+    Step,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("step")]
+struct CountData {
+    #[ping_pong]
+    a: Vec<u32>,
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(CountData)]
+struct CountBuffers {
+    #[ping_pong]
+    #[writeable]
+    a: PingPong<ShaderStorageBuffer>,
+}
+
+type CountShaderPlugin = ShaderPlugin<CountData, CountEntries, CountBuffers, 2, 1>;
+
+fn count_plugin(schedule: DispatchSchedule) -> CountShaderPlugin {
+    ShaderBuilder::default()
+        .initial_data(CountData { a: vec![0; 4] })
+        .shader(ShaderSource::wgsl(COUNT))
+        .on_update([Entry::new(CountEntries::Step, (4, 1, 1)).schedule(schedule)])
+        .build()
+}
+
+fn has_adapter() -> bool {
+    let instance = Instance::default();
+    [false, true].into_iter().any(|force_fallback_adapter| {
+        let options = RequestAdapterOptions {
+            force_fallback_adapter,
+            ..Default::default()
+        };
+        block_on(instance.request_adapter(&options)).is_some()
+    })
+}
+
+#[test]
+fn test_readback_follows_output_half() {
+    if !has_adapter() {
+        eprintln!("Skipped, no GPU or software adapter");
+        return;
+    }
+
+    // Odd and even swap counts, with one and several rounds per frame
+    for (frames, rounds) in [(1, 1), (2, 1), (3, 1), (4, 1), (2, 3), (3, 2)] {
+        let results = ComputeRunner::new(count_plugin(DispatchSchedule::Iterations(rounds)))
+            .iterations(frames)
+            .run()
+            .unwrap();
+
+        let a: Vec<u32> = results.get::<0, 0>().unwrap();
+        assert_eq!(
+            a,
+            [frames * rounds; 4],
+            "{frames} frames of {rounds} rounds"
+        );
+    }
+}
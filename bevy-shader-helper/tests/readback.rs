@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use bevy_shader_helper::{
+    ImageBuilder, ShaderBuilder,
+    bevy::{Image, Resource, render::render_resource::Extent3d},
+    internals::prelude::*,
+    texture_details::{D2, R32Float},
+};
+
+mod common;
+
+const FILL: &str = "
+@group(0) @binding(0) var texels: texture_storage_2d<r32float, write>;
+
+@compute @workgroup_size(1) fn fill(@builtin(global_invocation_id) id: vec3<u32>) {
+    textureStore(texels, id.xy, vec4(f32(id.y * 2u + id.x + 1u)));
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum FillEntries {
+    Fill,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("fill")]
+struct FillData {
+    #[texture(WriteOnly, R32Float, D2)]
+    texels: ImageBuilder<R32Float, D2>,
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(FillData)]
+struct FillBuffers {
+    #[writeable]
+    #[texture]
+    texels: WriteBuffer<Image>,
+}
+
+type FillShaderPlugin = ShaderPlugin<FillData, FillEntries, FillBuffers, 1, 1>;
+
+#[test]
+fn test_texture_readback_trims_rows() {
+    // Rows of 8 bytes, which the copy pads to 256
+    let size = Extent3d {
+        width: 2,
+        height: 2,
+        depth_or_array_layers: 1,
+    };
+    let plugin: FillShaderPlugin = ShaderBuilder::default()
+        .initial_data(FillData {
+            texels: size.into(),
+        })
+        .shader(ShaderSource::wgsl(FILL))
+        .on_update([Entry::new(FillEntries::Fill, (2, 2, 1))])
+        .build();
+    let Some(mut app) = common::headless_app(plugin) else {
+        return eprintln!("Skipped, no GPU or software adapter");
+    };
+    common::update_until(&mut app, |world| {
+        matches!(
+            world.get_resource::<ShaderStatus<FillEntries>>(),
+            Some(ShaderStatus::Ready)
+        )
+    });
+
+    let texels = Arc::new(Mutex::new(None));
+    let read = texels.clone();
+    let world = app.world_mut();
+    let buffers = world.resource::<FillBuffers>().clone();
+    world.spawn(buffers.readback_texels(move |data| {
+        read.lock().unwrap().get_or_insert(data);
+    }));
+    common::update_until(&mut app, |_| texels.lock().unwrap().is_some());
+
+    assert_eq!(texels.lock().unwrap().take(), Some(vec![1., 2., 3., 4.]));
+}
@@ -0,0 +1,197 @@
+use bevy_shader_helper::{
+    ShaderBuilder,
+    bevy::{
+        Resource,
+        render::{render_resource::Extent3d, settings::WgpuSettings, storage::ShaderStorageBuffer},
+    },
+    internals::prelude::*,
+};
+use wgpu::Backends;
+
+const ADD: &str = "
+@group(0) @binding(0) var<storage, read_write> a: array<u32>;
+
+@compute @workgroup_size(1) fn add(@builtin(global_invocation_id) id: vec3<u32>) {
+    a[id.x] += 1u;
+}
+";
+
+#[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+enum AddEntries {
+    Add,
+}
+
+#[derive(ShaderDataDetails, Clone)]
+#[entry("add")]
+struct AddData {
+    a: Vec<u32>,
+}
+
+#[derive(Resource, ExtractResource, Clone, BufferGroup)]
+#[data(AddData)]
+struct AddBuffers {
+    #[writeable]
+    a: ReadWriteBuffer<ShaderStorageBuffer>,
+}
+
+type AddShaderPlugin = ShaderPlugin<AddData, AddEntries, AddBuffers, 1, 1>;
+
+fn add_plugin(shader: &'static str) -> AddShaderPlugin {
+    ShaderBuilder::default()
+        .initial_data(AddData { a: vec![0; 4] })
+        .shader(ShaderSource::wgsl(shader))
+        .on_update([Entry::new(AddEntries::Add, (4, 1, 1))])
+        .build()
+}
+
+#[test]
+fn test_runner_iterations() {
+    let results = match ComputeRunner::new(add_plugin(ADD)).iterations(5).run() {
+        Err(RunnerError::NoAdapter) => return eprintln!("Skipped, no GPU or software adapter"),
+        results => results.unwrap(),
+    };
+
+    assert_eq!(results.get::<0, 0>(), Some(vec![5; 4]));
+}
+
+#[test]
+fn test_runner_conditions() {
+    // Every dispatched frame sees the condition it evaluated, the first one included
+    for (holds, expected) in [(true, 3), (false, 0)] {
+        let plugin: AddShaderPlugin = ShaderBuilder::default()
+            .initial_data(AddData { a: vec![0; 4] })
+            .shader(ShaderSource::wgsl(ADD))
+            .on_update([Entry::new(AddEntries::Add, (4, 1, 1)).run_if(move || holds)])
+            .build();
+        let results = match ComputeRunner::new(plugin).iterations(3).run() {
+            Err(RunnerError::NoAdapter) => return eprintln!("Skipped, no GPU or software adapter"),
+            results => results.unwrap(),
+        };
+
+        assert_eq!(results.get::<0, 0>(), Some(vec![expected; 4]));
+    }
+}
+
+// The pipeline cache defines the storage buffer limit for every shader
+const ADD_PAIRS: &str = "
+@group(0) @binding(0) var<storage, read_write> a: array<u32>;
+
+#ifdef AVAILABLE_STORAGE_BUFFER_BINDINGS
+@compute @workgroup_size(2)
+#else
+@compute @workgroup_size(1)
+#endif
+fn add(@builtin(global_invocation_id) id: vec3<u32>) {
+    a[id.x] += 1u;
+}
+";
+
+#[test]
+fn test_runner_processed_workgroup_sizes() {
+    let plugin: AddShaderPlugin = ShaderBuilder::default()
+        .initial_data(AddData { a: vec![0; 4] })
+        .shader(ShaderSource::wgsl(ADD_PAIRS))
+        .on_update([Entry::new(
+            AddEntries::Add,
+            Workgroups::Binding {
+                group: 0,
+                binding: 0,
+            },
+        )])
+        .build();
+    let results = match ComputeRunner::new(plugin).iterations(2).run() {
+        Err(RunnerError::NoAdapter) => return eprintln!("Skipped, no GPU or software adapter"),
+        results => results.unwrap(),
+    };
+
+    assert_eq!(results.get::<0, 0>(), Some(vec![2; 4]));
+}
+
+// Each `BufferGroup` derive imports `bevy_ecs`, so a second group lives in its own module
+mod store {
+    use bevy_shader_helper::{
+        ImageBuilder,
+        bevy::{Image, Resource},
+        internals::prelude::*,
+        texture_details::{D2, R32Uint},
+    };
+
+    pub const STORE: &str = "
+@group(0) @binding(0) var texels: texture_storage_2d<r32uint, write>;
+
+@compute @workgroup_size(1) fn store(@builtin(global_invocation_id) id: vec3<u32>) {
+    textureStore(texels, id.xy, vec4(id.x + 1u));
+}
+";
+
+    #[derive(ShaderEntry, Debug, PartialEq, Eq, Hash, Clone)]
+    pub enum StoreEntries {
+        Store,
+    }
+
+    #[derive(ShaderDataDetails, Clone)]
+    #[entry("store")]
+    pub struct StoreData {
+        #[texture(WriteOnly, R32Uint, D2)]
+        pub texels: ImageBuilder<R32Uint, D2>,
+    }
+
+    #[derive(Resource, ExtractResource, Clone, BufferGroup)]
+    #[data(StoreData)]
+    pub struct StoreBuffers {
+        #[writeable]
+        #[texture]
+        pub texels: WriteBuffer<Image>,
+    }
+}
+
+#[test]
+fn test_runner_texture() {
+    use store::{STORE, StoreBuffers, StoreData, StoreEntries};
+
+    let size = Extent3d {
+        width: 3,
+        height: 2,
+        depth_or_array_layers: 1,
+    };
+    let plugin: ShaderPlugin<StoreData, StoreEntries, StoreBuffers, 1, 1> =
+        ShaderBuilder::default()
+            .initial_data(StoreData {
+                texels: size.into(),
+            })
+            .shader(ShaderSource::wgsl(STORE))
+            .on_update([Entry::new(StoreEntries::Store, (3, 2, 1))])
+            .build();
+    let results = match ComputeRunner::new(plugin).run() {
+        Err(RunnerError::NoAdapter) => return eprintln!("Skipped, no GPU or software adapter"),
+        results => results.unwrap(),
+    };
+
+    // Rows of 12 bytes, without the padding to 256 of the copy
+    assert_eq!(results.bytes(0, 0).map(<[u8]>::len), Some(24));
+    assert_eq!(results.get::<0, 0>(), Some(vec![1, 2, 3, 1, 2, 3]));
+}
+
+#[test]
+fn test_runner_errors() {
+    let settings = WgpuSettings {
+        backends: Some(Backends::empty()),
+        ..Default::default()
+    };
+    let error = ComputeRunner::new(add_plugin(ADD))
+        .settings(settings)
+        .run()
+        .err();
+    assert_eq!(error, Some(RunnerError::NoAdapter));
+
+    let invalid = ADD.replace("1u", "1.");
+    let invalid: &'static str = invalid.leak();
+    match ComputeRunner::new(add_plugin(invalid)).run() {
+        Err(RunnerError::NoAdapter) => eprintln!("Skipped, no GPU or software adapter"),
+        Err(RunnerError::Shader(errors)) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].entry, Some(AddEntries::Add));
+        }
+        result => panic!("Expected a shader error, got {:?}", result.err()),
+    }
+}
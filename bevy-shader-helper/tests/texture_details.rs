@@ -0,0 +1,67 @@
+use bevy_shader_helper::{
+    ImageBuilder,
+    bevy::{
+        Image,
+        render::render_resource::{
+            Extent3d, TextureDimension, TextureFormat, TextureViewDimension,
+        },
+    },
+    texture_details::{D1, D2Array, D3, R32Uint, Rg8Unorm, Rgba32Float},
+};
+
+#[test]
+fn test_image_builder_pixels() {
+    let size = Extent3d {
+        width: 2,
+        height: 1,
+        depth_or_array_layers: 2,
+    };
+    let builder: ImageBuilder<Rgba32Float, D3> = size.into();
+    let pixels = [
+        [1., 0., 0., 1.],
+        [0., 1., 0., 1.],
+        [0., 0., 1., 1.],
+        [1.; 4],
+    ];
+    let image: Image = builder.pixels(pixels).into();
+
+    assert_eq!(image.texture_descriptor.format, TextureFormat::Rgba32Float);
+    assert_eq!(image.texture_descriptor.dimension, TextureDimension::D3);
+    let texels: Vec<f32> = image
+        .data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    assert_eq!(texels, pixels.concat());
+}
+
+#[test]
+fn test_image_builder_fill() {
+    let size = Extent3d {
+        width: 3,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let builder: ImageBuilder<Rg8Unorm, D1> = size.into();
+    let image: Image = builder.fill([255, 7]).into();
+
+    assert_eq!(image.texture_descriptor.format, TextureFormat::Rg8Unorm);
+    assert_eq!(image.texture_descriptor.dimension, TextureDimension::D1);
+    assert_eq!(image.data, [255, 7, 255, 7, 255, 7]);
+}
+
+#[test]
+fn test_image_builder_array() {
+    let size = Extent3d {
+        width: 1,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let builder: ImageBuilder<R32Uint, D2Array> = size.into();
+    let image: Image = builder.fill(3).into();
+
+    // Even a single layer is viewed as an array
+    assert_eq!(image.texture_descriptor.dimension, TextureDimension::D2);
+    let view = image.texture_view_descriptor.unwrap();
+    assert_eq!(view.dimension, Some(TextureViewDimension::D2Array));
+}
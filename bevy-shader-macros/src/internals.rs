@@ -1,3 +1,4 @@
 pub mod entries;
 pub mod binding;
-pub mod buffers;
\ No newline at end of file
+pub mod buffers;
+pub mod index;
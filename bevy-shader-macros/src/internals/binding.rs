@@ -1,46 +1,129 @@
 use proc_macro::TokenStream;
-use proc_macro2::TokenTree;
-use quote::{ToTokens, quote};
-use syn::{DeriveInput, Field, Meta, MetaList};
+use proc_macro2::{Ident, Literal, TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{Attribute, DeriveInput, Field, GenericArgument, LitStr, Meta, MetaList, PathArguments, Type};
+
+use super::index::{binding_indices, find_attr, ident_to_member, index_const};
 
 pub fn expand(input: TokenStream) -> TokenStream {
     let DeriveInput {
         ident, data, attrs, ..
     } = syn::parse_macro_input!(input as DeriveInput);
 
-    let entries: Vec<_> = attrs
+    let shader = match attrs.iter().find(|a| a.path().is_ident("shader")) {
+        Some(attr) => match attr.parse_args::<LitStr>() {
+            Ok(path) => Some(path),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        None => None,
+    };
+    let shader = shader.map(|path| {
+        quote! {
+            fn shader() -> Option<bevy_shader_helper::internals::source::ShaderSource> {
+                Some(#path.into())
+            }
+        }
+    });
+
+    let expanded_entries: Vec<_> = attrs
         .into_iter()
         .filter_map(|t| expand_entry(t.meta))
         .collect();
+    let entry_names = expanded_entries.iter().map(|(name, ..)| name);
+    let entry_labels = expanded_entries.iter().map(|(_, label, _)| label);
+    let entries: Vec<_> = expanded_entries.iter().map(|(.., entry)| entry).collect();
     let entry_count = entries.len();
 
     let rr = quote! { bevy_shader_helper::bevy::render::render_resource };
     let fields: Vec<_> = match data {
-        syn::Data::Struct(data) => data
-            .fields
-            .into_iter()
-            .map(|t| expand_field(t, &rr))
-            .collect(),
+        syn::Data::Struct(data) => data.fields.into_iter().collect(),
         _ => unimplemented!("Cannot expand non-struct into shader data"),
     };
-    let fields_count = fields.len();
+    if let Err(err) = fields.iter().try_for_each(validate_texture) {
+        return err.to_compile_error().into();
+    }
+    let access_checks: Vec<_> = fields.iter().filter_map(expand_access_check).collect();
+    let indices = match binding_indices(&fields) {
+        Ok(indices) => indices,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let members: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(count, f)| ident_to_member(f, count))
+        .collect();
+    let group_consts = members.iter().map(|m| index_const("group", m));
+    let binding_consts = members.iter().map(|m| index_const("binding", m));
+    let readbacks: Vec<_> = fields
+        .iter()
+        .zip(&indices)
+        .filter_map(|(field, index)| expand_readback(&ident, field, index.group, index.binding))
+        .collect();
+    let groups: Vec<_> = indices.iter().map(|i| i.group).collect();
+    let bindings: Vec<_> = indices.iter().map(|i| i.binding).collect();
+    // Both halves of a ping pong field share the same layout
+    let mut layout_entries = vec![];
+    for (field, index) in fields.into_iter().zip(&indices) {
+        let size = expand_element_size(&field, &rr);
+        let entry = expand_field(field, &rr).to_token_stream();
+        for binding in index.bindings() {
+            layout_entries.push((index.group, binding, entry.clone(), size.clone()));
+        }
+    }
+    let entry_groups = layout_entries.iter().map(|(group, ..)| group);
+    let entry_bindings = layout_entries.iter().map(|(_, binding, ..)| binding);
+    let fields = layout_entries.iter().map(|(_, _, entry, _)| entry);
+    let element_sizes = layout_entries.iter().map(|(.., size)| size);
+    let fields_count = layout_entries.len();
 
     let handle = quote! { bevy_shader_helper::bevy::Handle };
 
     let expanded = quote! {
+    #[allow(non_upper_case_globals)]
+    impl #ident {
+        #(
+            #[doc(hidden)]
+            pub const #group_consts: u32 = #groups;
+            #[doc(hidden)]
+            pub const #binding_consts: u32 = #bindings;
+        )*
+    }
+
+    #(#readbacks)*
+
+    #(#access_checks)*
+
     impl ShaderDataDetails<#fields_count, #entry_count> for #ident {
         fn buffer_entries(stage: #rr::ShaderStages) -> #rr::BindGroupLayoutEntries<#fields_count> {
-            #rr::BindGroupLayoutEntries::sequential(
+            #rr::BindGroupLayoutEntries::with_indices(
                 stage,
                 (
-                    #(#fields),*
+                    #((#entry_bindings, #fields),)*
                 ),
             )
         }
 
+        fn groups() -> [u32; #fields_count] {
+            [#(#entry_groups),*]
+        }
+
+        fn element_sizes() -> [u64; #fields_count] {
+            [#(#element_sizes),*]
+        }
+
+        #shader
+
+        fn entry_names() -> [&'static str; #entry_count] {
+            [#(#entry_names),*]
+        }
+
+        fn entry_labels() -> [Option<&'static str>; #entry_count] {
+            [#(#entry_labels),*]
+        }
+
         fn entries(
             pipeline_cache: &#rr::PipelineCache,
-            layout: #rr::BindGroupLayout,
+            layouts: &[#rr::BindGroupLayout],
             shader: #handle<#rr::Shader>,
         ) -> [#rr::CachedComputePipelineId; #entry_count] {
             [
@@ -52,7 +135,7 @@ pub fn expand(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
-fn expand_entry(meta: Meta) -> Option<impl ToTokens> {
+fn expand_entry(meta: Meta) -> Option<(Literal, TokenStream2, TokenStream2)> {
     let meta = meta.require_list().ok()?;
     if !meta.path.is_ident("entry") {
         return None;
@@ -65,21 +148,223 @@ fn expand_entry(meta: Meta) -> Option<impl ToTokens> {
 
     let name = args.next()?;
 
-    let label = if let Some(lit) = args.next() {
-        quote! { Some(#lit.into()) }
+    let (label, pipeline_label) = if let Some(lit) = args.next() {
+        (quote! { Some(#lit) }, quote! { Some(#lit.into()) })
     } else {
-        quote! { None }
+        (quote! { None }, quote! { None })
     };
 
     // eprintln!("{:#?}", args);
-    Some(
-        quote! { Self::create_entry(pipeline_cache, layout.clone(), shader.clone(), #name, #label) },
-    )
+    let entry = quote! {
+        Self::create_entry(pipeline_cache, layouts, shader.clone(), #name, #pipeline_label)
+    };
+    Some((name, label, entry))
+}
+
+/// Size of one element of a buffer field, arrays count their items and anything else counts as one
+fn expand_element_size(field: &Field, rr: &impl ToTokens) -> TokenStream2 {
+    // Textures are measured in texels instead
+    if find_attr(field, "texture").is_some() {
+        return quote! { 0 };
+    }
+    let item = match &field.ty {
+        Type::Array(array) => Some(&*array.elem),
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .filter(|segment| segment.ident == "Vec")
+            .and_then(|segment| match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.first(),
+                _ => None,
+            })
+            .and_then(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }),
+        _ => None,
+    };
+    // A single item array includes the padding between items
+    let ty = match item {
+        Some(item) => quote! { [#item; 1] },
+        None => field.ty.to_token_stream(),
+    };
+    quote! { <#ty as #rr::ShaderType>::min_size().get() }
+}
+
+/// The type a readback of the field decodes into, fields the shader cannot write have none
+fn expand_readback(ident: &Ident, field: &Field, group: u32, binding: u32) -> Option<TokenStream2> {
+    if find_attr(field, "read_only").is_some() || find_attr(field, "uniform").is_some() {
+        return None;
+    }
+
+    let helper = quote! { bevy_shader_helper::internals::readback };
+    let (output, read) = match find_attr(field, "texture") {
+        Some(attr) => {
+            let format = match &attr.meta {
+                Meta::List(meta) => meta
+                    .tokens
+                    .clone()
+                    .into_iter()
+                    .filter_map(|t| match t {
+                        TokenTree::Ident(ident) => Some(ident),
+                        _ => None,
+                    })
+                    .nth(1),
+                _ => None,
+            }?;
+            let details = quote! { bevy_shader_helper::texture_details };
+            (
+                quote! { Vec<<#details::#format as #details::ToTextureFormat>::Pixel> },
+                quote! { #helper::read_texels(bytes) },
+            )
+        }
+        None => {
+            let ty = &field.ty;
+            (quote! { #ty }, quote! { #helper::read_shader_type(bytes) })
+        }
+    };
+
+    Some(quote! {
+        impl #helper::ReadbackData<#group, #binding> for #ident {
+            type Output = #output;
+
+            fn read(bytes: &[u8]) -> Self::Output {
+                #read
+            }
+        }
+    })
+}
+
+/// Rejects `#[texture(access, format, view)]` attributes no storage texture can have, and
+/// `ImageBuilder` fields whose format or dimension disagree with the attribute
+///
+/// Formats are resolved to the markers of `texture_details`, which only exist for storage formats,
+/// and [`expand_access_check`] bounds them by the access
+fn validate_texture(field: &Field) -> syn::Result<()> {
+    let Some(attr) = find_attr(field, "texture") else {
+        return Ok(());
+    };
+    let args = texture_args(attr);
+    let [access, format, view] = args.as_slice() else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "Expected #[texture(access, format, view dimension)]",
+        ));
+    };
+
+    if !["ReadOnly", "WriteOnly", "ReadWrite"].contains(&access.to_string().as_str()) {
+        return Err(syn::Error::new_spanned(
+            access,
+            format!(
+                "{access} is not a storage texture access, use ReadOnly, WriteOnly or ReadWrite"
+            ),
+        ));
+    }
+    match view.to_string().as_str() {
+        "D1" | "D2" | "D2Array" | "D3" => {}
+        "Cube" | "CubeArray" => {
+            return Err(syn::Error::new_spanned(
+                view,
+                "Storage textures cannot be viewed as cube maps",
+            ));
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                view,
+                format!("{view} is not a texture view dimension"),
+            ));
+        }
+    }
+
+    // Other field types are converted into an image by the user
+    let Some((builder_format, builder_dimension)) = image_builder_args(&field.ty) else {
+        return Ok(());
+    };
+    if builder_format != format {
+        return Err(syn::Error::new_spanned(
+            builder_format,
+            format!(
+                "The ImageBuilder format {builder_format} differs from the texture format {format}"
+            ),
+        ));
+    }
+    if builder_dimension != view {
+        return Err(syn::Error::new_spanned(
+            builder_dimension,
+            format!(
+                "A {view} view needs an ImageBuilder of dimension {view}, not {builder_dimension}"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Requires the format marker to support reading for `ReadOnly` and `ReadWrite` textures, every
+/// storage format can be written
+fn expand_access_check(field: &Field) -> Option<TokenStream2> {
+    let args = texture_args(find_attr(field, "texture")?);
+    let [access, format, _] = args.as_slice() else {
+        return None;
+    };
+    let access_trait = match access.to_string().as_str() {
+        "ReadOnly" => quote! { ReadableStorage },
+        "ReadWrite" => quote! { ReadWriteStorage },
+        _ => return None,
+    };
+
+    // Spelled out inside the spanned quote, so the error points at the format
+    Some(quote_spanned! {format.span()=>
+        const _: () = {
+            fn supports_access<F: bevy_shader_helper::texture_details::#access_trait>() {}
+            let _ = supports_access::<bevy_shader_helper::texture_details::#format>;
+        };
+    })
+}
+
+fn texture_args(attr: &Attribute) -> Vec<Ident> {
+    match &attr.meta {
+        Meta::List(meta) => meta
+            .tokens
+            .clone()
+            .into_iter()
+            .filter_map(|t| match t {
+                TokenTree::Ident(ident) => Some(ident),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The last path segment of both type arguments of an `ImageBuilder<F, D>`
+fn image_builder_args(ty: &Type) -> Option<(&Ident, &Ident)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut idents = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(Type::Path(path)) => path.path.segments.last().map(|s| &s.ident),
+        _ => None,
+    });
+    match (
+        segment.ident == "ImageBuilder",
+        idents.next(),
+        idents.next(),
+    ) {
+        (true, Some(format), Some(dimension)) => Some((format, dimension)),
+        _ => None,
+    }
 }
 
 enum FieldAttr {
     Texture(MetaList),
     ReadOnly,
+    Uniform,
 }
 
 fn expand_field(field: Field, rr: &impl ToTokens) -> impl ToTokens {
@@ -91,6 +376,8 @@ fn expand_field(field: Field, rr: &impl ToTokens) -> impl ToTokens {
             }
         } else if a.path().is_ident("read_only") {
             Some(FieldAttr::ReadOnly)
+        } else if a.path().is_ident("uniform") {
+            Some(FieldAttr::Uniform)
         } else {
             None
         }
@@ -114,15 +401,20 @@ fn expand_field(field: Field, rr: &impl ToTokens) -> impl ToTokens {
                 let format = &a[1];
                 let dim = &a[2];
                 // eprintln!("{:#?}", a);
+                let details = quote! { bevy_shader_helper::texture_details };
+                let format_details = quote_spanned! {format.span()=>
+                    <#details::#format as #details::ToTextureFormat>::texture_format()
+                };
                 quote! {
                         #rr::IntoBindGroupLayoutEntryBuilder::into_bind_group_layout_entry_builder(#rr::BindingType::StorageTexture {
                             access: #rr::StorageTextureAccess::#access,
-                            format: #rr::TextureFormat::#format,
+                            format: #format_details,
                             view_dimension: #rr::TextureViewDimension::#dim,
                         })
                 }
             }
             FieldAttr::ReadOnly => quote! { #bind_types::storage_buffer_read_only::<#ty>(false) },
+            FieldAttr::Uniform => quote! { #bind_types::uniform_buffer::<#ty>(false) },
         }
     } else {
         quote! { #bind_types::storage_buffer::<#ty>(false) }
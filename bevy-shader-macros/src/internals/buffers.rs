@@ -1,7 +1,12 @@
 use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{ToTokens, quote};
 use syn::{DeriveInput, Field, Member};
 
+use super::index::{
+    BindingIndex, binding_indices, find_attr, ident_to_member, index_const, is_ping_pong,
+};
+
 pub fn expand(input: TokenStream) -> TokenStream {
     let DeriveInput {
         ident, data, attrs, ..
@@ -30,37 +35,142 @@ pub fn expand(input: TokenStream) -> TokenStream {
     let commands = quote! { bevy_shader_helper::bevy::Commands };
     let buffers = quote! { bevy_shader_helper::internals::buffers };
 
-    let fields: (Vec<_>, Vec<_>) = match data {
-        syn::Data::Struct(data) => data
-            .fields
-            .into_iter()
-            .enumerate()
-            .map(|(count, f)| {
-                (
-                    expand_entries(f.clone(), &buffers, count),
-                    expand_resources(f, &buffers, count),
-                )
-            })
-            .unzip(),
+    let fields: Vec<_> = match data {
+        syn::Data::Struct(data) => data.fields.into_iter().collect(),
         _ => unimplemented!("Cannot expand non-struct into buffer group"),
     };
-    let entries = fields.0;
-    let resources = fields.1;
+    let indices = match binding_indices(&fields) {
+        Ok(indices) => indices,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // The data derive exposes its indices, so a mismatch between both derives fails to compile
+    let checks: Vec<_> = fields
+        .iter()
+        .zip(&indices)
+        .enumerate()
+        .map(|(count, (f, index))| {
+            let member = ident_to_member(f, count);
+            let group_const = index_const("group", &member);
+            let binding_const = index_const("binding", &member);
+            let (group, binding) = (index.group, index.binding);
+            let message = format!(
+                "`{}` must use the same group and binding index in {} and {}",
+                member.to_token_stream(),
+                ident,
+                data_type
+            );
+            quote! {
+                const _: () = assert!(
+                    #data_type::#group_const == #group && #data_type::#binding_const == #binding,
+                    #message
+                );
+            }
+        })
+        .collect();
+
+    // Every ping pong buffer of the group swaps at the same time
+    let ping_pong = fields.iter().any(is_ping_pong).then(|| {
+        quote! {
+            fn ping_pong() -> bool {
+                true
+            }
+        }
+    });
+
+    let indirect = match expand_indirect(&fields, &data_type, &indices, &buffers) {
+        Ok(indirect) => indirect,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let readbacks: Vec<_> = fields
+        .iter()
+        .zip(&indices)
+        .enumerate()
+        .filter_map(|(count, (f, index))| expand_readback(f, &data_type, index, count))
+        .collect();
+    let writeable: Vec<_> = fields
+        .iter()
+        .zip(&indices)
+        .enumerate()
+        .filter_map(|(count, (f, index))| expand_writeable(f, index, count))
+        .collect();
+
+    let mut entries = vec![];
+    let mut ids = vec![];
+    let mut extents = vec![];
+    let mut binding_assets = vec![];
+    let mut groups = vec![];
+    let mut bindings = vec![];
+    let mut resources = vec![];
+    for (count, (f, index)) in fields.into_iter().zip(&indices).enumerate() {
+        for (binding, (entry, id, extent, asset)) in index
+            .bindings()
+            .into_iter()
+            .zip(expand_entries(&f, &buffers, count))
+        {
+            groups.push(index.group);
+            bindings.push(binding);
+            entries.push(entry);
+            ids.push(id);
+            extents.push(extent);
+            binding_assets.push(asset);
+        }
+        resources.push(expand_resources(f, &buffers, count));
+    }
     let size = entries.len();
     quote! {
      // I do not know why this is needed...
     use bevy_shader_helper::bevy::bevy_ecs;
+    #(#checks)*
+    impl #ident {
+        #(#readbacks)*
+    }
     impl BufferGroup<#data_type, #size> for #ident {
+        #[allow(unused_variables)]
         fn get_bindings<'a>(
             &'a self,
             buffers: &'a #render::render_asset::RenderAssets<#render::storage::GpuShaderStorageBuffer>,
             images: &'a #render::render_asset::RenderAssets<#render::texture::GpuImage>,
+            swapped: bool,
         ) -> #rr::BindGroupEntries<'a, #size> {
-            #rr::BindGroupEntries::sequential((
-                #(#entries),*
+            #rr::BindGroupEntries::with_indices((
+                #((#bindings, #entries),)*
             ))
         }
 
+        fn groups() -> [u32; #size] {
+            [#(#groups),*]
+        }
+
+        fn resource_ids(
+            &self,
+            buffers: &#render::render_asset::RenderAssets<#render::storage::GpuShaderStorageBuffer>,
+            images: &#render::render_asset::RenderAssets<#render::texture::GpuImage>,
+        ) -> Vec<Option<#buffers::ResourceId>> {
+            vec![#(#ids),*]
+        }
+
+        fn extents(
+            &self,
+            buffers: &#render::render_asset::RenderAssets<#render::storage::GpuShaderStorageBuffer>,
+            images: &#render::render_asset::RenderAssets<#render::texture::GpuImage>,
+        ) -> Vec<Option<#buffers::BindingExtent>> {
+            vec![#(#extents),*]
+        }
+
+        #[allow(unused_variables)]
+        fn assets(&self, swapped: bool) -> Vec<#buffers::BindingAsset> {
+            vec![#(#binding_assets),*]
+        }
+
+        #ping_pong
+
+        fn readbacks(&self) -> Vec<#buffers::BindingReadback<Self>> {
+            vec![#(#writeable),*]
+        }
+
+        #indirect
+
         fn insert_resources(
             commands: &mut #commands,
             buffers: &mut #assets<#render::storage::ShaderStorageBuffer>,
@@ -76,7 +186,12 @@ pub fn expand(input: TokenStream) -> TokenStream {
     .into()
 }
 
-fn expand_entries(field: Field, buffers: &impl ToTokens, count: usize) -> impl ToTokens {
+/// The binding of every bound resource along with the id, extent and asset of the resource behind it
+fn expand_entries(
+    field: &Field,
+    buffers: &impl ToTokens,
+    count: usize,
+) -> Vec<(TokenStream2, TokenStream2, TokenStream2, TokenStream2)> {
     let texture = field.attrs.iter().any(|a| {
         a.meta
             .require_path_only().is_ok_and(|t| t.is_ident("texture"))
@@ -88,7 +203,145 @@ fn expand_entries(field: Field, buffers: &impl ToTokens, count: usize) -> impl T
         quote! {buffers}
     };
 
-    quote! {#buffers::HandleIntoBinding::binding(&self.#ident, #buffer)}
+    if is_ping_pong(field) {
+        [false, true]
+            .into_iter()
+            .map(|output| {
+                (
+                    quote! {#buffers::PingPongBinding::binding(&self.#ident, #buffer, swapped, #output)},
+                    quote! {#buffers::PingPongBinding::resource_id(&self.#ident, #buffer, false, #output)},
+                    quote! {#buffers::PingPongBinding::extent(&self.#ident, #buffer, false, #output)},
+                    quote! {#buffers::PingPongBinding::asset(&self.#ident, swapped, #output)},
+                )
+            })
+            .collect()
+    } else {
+        vec![(
+            quote! {#buffers::HandleIntoBinding::binding(&self.#ident, #buffer)},
+            quote! {#buffers::HandleIntoBinding::resource_id(&self.#ident, #buffer)},
+            quote! {#buffers::HandleIntoBinding::extent(&self.#ident, #buffer)},
+            quote! {#buffers::HandleIntoBinding::asset(&self.#ident)},
+        )]
+    }
+}
+
+/// Spawning the helper reads the field back, and hands the decoded data of the matching data field to `callback`
+fn expand_readback(
+    field: &Field,
+    data_type: &impl ToTokens,
+    index: &BindingIndex,
+    count: usize,
+) -> Option<TokenStream2> {
+    let writeable = field.attrs.iter().any(|a| {
+        a.meta
+            .require_path_only().is_ok_and(|t| t.is_ident("writeable"))
+    });
+    if !writeable {
+        return None;
+    }
+
+    let member = ident_to_member(field, count);
+    let name = match &member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    };
+    let helper = Ident::new(
+        &format!("readback_{}", name.trim_start_matches("r#")),
+        Span::call_site(),
+    );
+    let readback = quote! { bevy_shader_helper::internals::readback };
+    let (group, binding) = (index.group, index.binding);
+    let data = quote! { <#data_type as #readback::ReadbackData<#group, #binding>> };
+    let on_readback = quote! { #readback::OnReadback::new(#data::read, callback) };
+    let bundle = if is_ping_pong(field) {
+        quote! {
+            let (readback, retarget) = self.#member.readback::<Self>();
+            (readback, retarget, #on_readback)
+        }
+    } else {
+        quote! {
+            (bevy_shader_helper::internals::buffers::ReadableBuffer::readback(&self.#member), #on_readback)
+        }
+    };
+
+    Some(quote! {
+        pub fn #helper(
+            &self,
+            callback: impl FnMut(#data::Output) + Send + Sync + 'static,
+        ) -> impl bevy_shader_helper::bevy::bevy_ecs::bundle::Bundle {
+            #bundle
+        }
+    })
+}
+
+/// The readback of a `#[writeable]` field, along with its group and binding
+fn expand_writeable(field: &Field, index: &BindingIndex, count: usize) -> Option<TokenStream2> {
+    find_attr(field, "writeable")?;
+
+    let member = ident_to_member(field, count);
+    let (group, binding) = (index.group, index.binding);
+    let readback = if is_ping_pong(field) {
+        quote! {
+            let (readback, retarget) = self.#member.readback::<Self>();
+            ((#group, #binding), readback, Some(retarget))
+        }
+    } else {
+        quote! {
+            let readback = bevy_shader_helper::internals::buffers::ReadableBuffer::readback(&self.#member);
+            ((#group, #binding), readback, None)
+        }
+    };
+    Some(quote! { { #readback } })
+}
+
+/// The buffers `#[indirect]` marks as dispatch arguments
+fn expand_indirect(
+    fields: &[Field],
+    data_type: &impl ToTokens,
+    indices: &[BindingIndex],
+    buffers: &impl ToTokens,
+) -> syn::Result<Option<TokenStream2>> {
+    let render = quote! { bevy_shader_helper::bevy::render };
+    let mut bindings = vec![];
+    let mut sizes = vec![];
+    let mut gpu_buffers = vec![];
+    for (count, (field, index)) in fields.iter().zip(indices).enumerate() {
+        let Some(attr) = find_attr(field, "indirect") else {
+            continue;
+        };
+        // Each half would need its own arguments, while entries only name a single binding
+        if is_ping_pong(field) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[indirect] buffers cannot be ping pong buffers",
+            ));
+        }
+        let member = ident_to_member(field, count);
+        let (group, binding) = (index.group, index.binding);
+        bindings.push(quote! { (#group, #binding) });
+        sizes.push(quote! { #render::render_resource::ShaderType::size(&data.#member).get() });
+        gpu_buffers.push(quote! { #buffers::gpu_buffer(buffers, &self.#member.handle) });
+    }
+    if bindings.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(quote! {
+        fn indirect_bindings() -> Vec<(u32, u32)> {
+            vec![#(#bindings),*]
+        }
+
+        fn indirect_sizes(data: &#data_type) -> Vec<u64> {
+            vec![#(#sizes),*]
+        }
+
+        fn indirect_buffers(
+            &self,
+            buffers: &#render::render_asset::RenderAssets<#render::storage::GpuShaderStorageBuffer>,
+        ) -> Vec<Option<#render::render_resource::Buffer>> {
+            vec![#(#gpu_buffers),*]
+        }
+    }))
 }
 
 fn expand_resources(field: Field, buffers: &impl ToTokens, count: usize) -> impl ToTokens {
@@ -100,21 +353,31 @@ fn expand_resources(field: Field, buffers: &impl ToTokens, count: usize) -> impl
         a.meta
             .require_path_only().is_ok_and(|t| t.is_ident("writeable"))
     });
-    let ident = ident_to_member(field, count);
+    let uniform = field.attrs.iter().any(|a| {
+        a.meta
+            .require_path_only().is_ok_and(|t| t.is_ident("uniform"))
+    });
+    let indirect = find_attr(&field, "indirect").is_some();
+    let ident = ident_to_member(&field, count);
 
-    let create = if texture {
-        quote! {create_texture_buffer(images, d.#ident, #writeable)}
-    } else {
-        quote! {create_storage_buffer(buffers, d.#ident, #writeable)}
+    let create = |data| {
+        if texture {
+            quote! {create_texture_buffer(images, #data, #writeable)}
+        } else if indirect {
+            quote! {create_indirect_buffer(buffers, #data)}
+        } else if uniform {
+            quote! {create_uniform_buffer(buffers, #data)}
+        } else {
+            quote! {create_storage_buffer(buffers, #data, #writeable)}
+        }
     };
 
-    quote! {#ident: #buffers::#create.into()}
-}
-
-fn ident_to_member(field: Field, count: usize) -> Member {
-    if let Some(ident) = field.ident {
-        Member::Named(ident)
+    if is_ping_pong(&field) {
+        let front = create(quote! {d.#ident.clone()});
+        let back = create(quote! {d.#ident});
+        quote! {#ident: #buffers::PingPong::new(#buffers::#front, #buffers::#back)}
     } else {
-        Member::Unnamed(count.into())
+        let create = create(quote! {d.#ident});
+        quote! {#ident: #buffers::#create.into()}
     }
 }
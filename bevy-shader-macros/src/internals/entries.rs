@@ -5,11 +5,19 @@ use syn::{DeriveInput, Variant};
 pub fn expand(input: TokenStream) -> TokenStream {
     let DeriveInput { ident, data, .. } = syn::parse_macro_input!(input as DeriveInput);
 
-    let variants = match data {
-        syn::Data::Enum(data_enum) => data_enum.variants.into_iter().enumerate().map(enumify), //.enumerate(),
+    let variants: Vec<_> = match data {
+        syn::Data::Enum(data_enum) => data_enum.variants.into_iter().collect(),
         _ => unimplemented!("Shader Entry must be a enum"),
         // TODO: consider implementing for other types, to allow for more a custom use of the helper
     };
+    let names: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            quote! { Self::#ident => stringify!(#ident) }
+        })
+        .collect();
+    let variants = variants.into_iter().enumerate().map(enumify);
 
     let expanded = quote! {
         impl ShaderEntry for #ident {
@@ -18,6 +26,12 @@ pub fn expand(input: TokenStream) -> TokenStream {
                     #(Self::#variants),*
                 }
             }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(#names),*
+                }
+            }
         }
     };
 
@@ -0,0 +1,103 @@
+use proc_macro2::{Ident, Span};
+use syn::{Attribute, Field, LitInt, Member, spanned::Spanned};
+
+pub struct BindingIndex {
+    pub group: u32,
+    pub binding: u32,
+    /// Ping pong fields take two bindings, the input half followed by the output half
+    pub ping_pong: bool,
+}
+
+impl BindingIndex {
+    pub fn bindings(&self) -> Vec<u32> {
+        if self.ping_pong {
+            vec![self.binding, self.binding + 1]
+        } else {
+            vec![self.binding]
+        }
+    }
+
+    fn next(&self) -> u32 {
+        self.binding + self.bindings().len() as u32
+    }
+}
+
+/// Index of every field, taken from `#[group(n)]` and `#[binding(n)]`
+///
+/// Fields without a group use group 0, fields without a binding follow the previous field of the same group
+pub fn binding_indices<'a>(
+    fields: impl IntoIterator<Item = &'a Field>,
+) -> syn::Result<Vec<BindingIndex>> {
+    let mut indices: Vec<BindingIndex> = vec![];
+    for field in fields {
+        let group_attr = find_attr(field, "group");
+        let binding_attr = find_attr(field, "binding");
+        let group = match group_attr {
+            Some(attr) => parse_index(attr)?,
+            None => 0,
+        };
+        let binding = match binding_attr {
+            Some(attr) => parse_index(attr)?,
+            None => indices
+                .iter()
+                .rev()
+                .find(|i| i.group == group)
+                .map_or(0, BindingIndex::next),
+        };
+        let index = BindingIndex {
+            group,
+            binding,
+            ping_pong: is_ping_pong(field),
+        };
+        let bindings = index.bindings();
+        if indices
+            .iter()
+            .filter(|i| i.group == group)
+            .any(|i| i.bindings().iter().any(|b| bindings.contains(b)))
+        {
+            let span = binding_attr
+                .or(group_attr)
+                .map_or(field.span(), |a| a.span());
+            Err(syn::Error::new(
+                span,
+                format!("Binding index {binding} of group {group} is used by multiple fields"),
+            ))?
+        }
+        indices.push(index);
+    }
+
+    Ok(indices)
+}
+
+pub fn is_ping_pong(field: &Field) -> bool {
+    find_attr(field, "ping_pong").is_some()
+}
+
+pub fn find_attr<'a>(field: &'a Field, name: &str) -> Option<&'a Attribute> {
+    field.attrs.iter().find(|a| a.path().is_ident(name))
+}
+
+fn parse_index(attr: &Attribute) -> syn::Result<u32> {
+    attr.parse_args::<LitInt>()?.base10_parse()
+}
+
+/// Hidden const on the data struct, which lets the `BufferGroup` derive check it uses the same indices
+pub fn index_const(kind: &str, member: &Member) -> Ident {
+    let name = match member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    };
+
+    Ident::new(
+        &format!("__{kind}_{}", name.trim_start_matches("r#")),
+        Span::call_site(),
+    )
+}
+
+pub fn ident_to_member(field: &Field, count: usize) -> Member {
+    if let Some(ident) = &field.ident {
+        Member::Named(ident.clone())
+    } else {
+        Member::Unnamed(count.into())
+    }
+}
@@ -9,13 +9,13 @@ pub fn shader_entry(input: TokenStream) -> TokenStream {
 }
 
 // TODO: restrict ShaderDataDetails to structs which impl Clone
-#[proc_macro_derive(ShaderDataDetails, attributes(entry, read_only, texture))]
+#[proc_macro_derive(ShaderDataDetails, attributes(shader, entry, read_only, texture, uniform, binding, group, ping_pong))]
 pub fn shader_data_details(input: TokenStream) -> TokenStream {
     internals::binding::expand(input)
 }
 
 // TODO: restrict BufferGroup to structs which impl Resource, ExtractResource and which types are all Buffer Types
-#[proc_macro_derive(BufferGroup, attributes(data, writeable, texture))]
+#[proc_macro_derive(BufferGroup, attributes(data, writeable, texture, uniform, indirect, binding, group, ping_pong))]
 pub fn buffer_group(input: TokenStream) -> TokenStream {
     internals::buffers::expand(input)
 }
\ No newline at end of file
@@ -1,18 +1,28 @@
 use bevy_shader_helper::{
-    bevy::{Image, render::storage::ShaderStorageBuffer, Resource},
-    internals::prelude::{BufferGroup, ReadBuffer, ReadWriteBuffer},
+    bevy::{
+        Image, Resource,
+        render::{render_resource::ShaderStages, storage::ShaderStorageBuffer},
+    },
+    internals::prelude::{
+        BufferGroup, PingPong, ReadBuffer, ReadWriteBuffer, ShaderDataDetails, UniformBuffer,
+    },
 };
 
 #[test]
 fn test_buffer_macro() {
-    #[derive(Clone)]
+    #[allow(dead_code)]
+    #[derive(Clone, ShaderDataDetails)]
     struct HelloData {
         a: u32,
         b: u32,
         c: u32,
+        #[texture(ReadWrite, R32Float, D2)]
         d: Image,
+        #[uniform]
+        e: f32,
     }
 
+    #[allow(dead_code)]
     #[derive(Resource, BufferGroup)]
     #[data(HelloData)]
     pub struct HelloBuffers {
@@ -23,6 +33,36 @@ fn test_buffer_macro() {
         #[writeable]
         #[texture]
         pub d: ReadWriteBuffer<Image>,
+        #[uniform]
+        pub e: UniformBuffer<ShaderStorageBuffer>,
+    }
+}
+
+#[test]
+fn test_buffer_macro_indices() {
+    #[allow(dead_code)]
+    #[derive(Clone, ShaderDataDetails)]
+    struct HelloData {
+        #[binding(2)]
+        a: u32,
+        #[read_only]
+        b: u32,
+        #[binding(0)]
+        #[uniform]
+        c: f32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Resource, BufferGroup)]
+    #[data(HelloData)]
+    pub struct HelloBuffers {
+        #[binding(2)]
+        #[writeable]
+        pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+        pub b: ReadBuffer<ShaderStorageBuffer>,
+        #[binding(0)]
+        #[uniform]
+        pub c: UniformBuffer<ShaderStorageBuffer>,
     }
 }
 
@@ -35,3 +75,64 @@ fn test_buffer_macro() {
 //     #[data(HelloData)]
 //     pub struct HelloBuffers(#[writeable] ReadWriteBuffer<ShaderStorageBuffer>);
 // }
+
+#[test]
+fn test_buffer_macro_ping_pong() {
+    #[allow(dead_code)]
+    #[derive(Clone, ShaderDataDetails)]
+    struct HelloData {
+        #[ping_pong]
+        a: Vec<u32>,
+        #[read_only]
+        b: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Resource, BufferGroup)]
+    #[data(HelloData)]
+    pub struct HelloBuffers {
+        #[ping_pong]
+        #[writeable]
+        pub a: PingPong<ShaderStorageBuffer>,
+        pub b: ReadBuffer<ShaderStorageBuffer>,
+    }
+
+    assert_eq!(HelloBuffers::groups().len(), 3);
+    assert_eq!(
+        HelloData::buffer_entries(ShaderStages::COMPUTE)
+            .iter()
+            .map(|entry| entry.binding)
+            .collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+}
+
+#[test]
+fn test_buffer_macro_indirect() {
+    #[allow(dead_code)]
+    #[derive(Clone, ShaderDataDetails)]
+    struct HelloData {
+        a: Vec<u32>,
+        #[binding(3)]
+        args: [u32; 3],
+    }
+
+    #[allow(dead_code)]
+    #[derive(Resource, BufferGroup)]
+    #[data(HelloData)]
+    pub struct HelloBuffers {
+        #[writeable]
+        pub a: ReadWriteBuffer<ShaderStorageBuffer>,
+        #[binding(3)]
+        #[indirect]
+        #[writeable]
+        pub args: ReadWriteBuffer<ShaderStorageBuffer>,
+    }
+
+    assert_eq!(HelloBuffers::indirect_bindings(), [(0, 3)]);
+    let data = HelloData {
+        a: vec![0; 4],
+        args: [1, 1, 1],
+    };
+    assert_eq!(HelloBuffers::indirect_sizes(&data), [12]);
+}
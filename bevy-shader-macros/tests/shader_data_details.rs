@@ -1,10 +1,11 @@
 use bevy_shader_helper::{
-    bevy::render::render_resource, internals::prelude::ShaderDataDetails, texture_details::{R32Float, D2}, ImageBuilder
+    bevy::render::render_resource, internals::prelude::{ReadbackData, ShaderDataDetails, ShaderSource}, texture_details::{R32Float, D2}, ImageBuilder
 };
 
 #[test]
 fn test_data_macro() {
     #[derive(Clone, ShaderDataDetails)]
+    #[shader("shaders/hello.wgsl")]
     #[entry("main")]
     #[entry("update", "label")]
     // #[entry]
@@ -14,8 +15,135 @@ fn test_data_macro() {
         pub _b: u32,
         #[texture(ReadWrite, R32Float, D2)]
         pub _c: ImageBuilder<R32Float, D2>,
+        #[uniform]
+        pub _d: u32,
     }
 
     let bind_group = HelloData::buffer_entries(render_resource::ShaderStages::COMPUTE);
-    assert_eq!(3, bind_group.len());
+    assert_eq!(4, bind_group.len());
+    assert!(matches!(
+        bind_group[3].ty,
+        render_resource::BindingType::Buffer {
+            ty: render_resource::BufferBindingType::Uniform,
+            ..
+        }
+    ));
+    assert!(matches!(
+        HelloData::shader(),
+        Some(ShaderSource::Path(path)) if path.path().to_str() == Some("shaders/hello.wgsl")
+    ));
+}
+
+#[test]
+fn test_data_macro_indices() {
+    #[derive(Clone, ShaderDataDetails)]
+    #[entry("main")]
+    pub struct HelloData {
+        #[binding(3)]
+        pub _a: Vec<u32>,
+        #[read_only]
+        pub _b: u32,
+        #[binding(0)]
+        #[uniform]
+        pub _c: u32,
+    }
+
+    let bind_group = HelloData::buffer_entries(render_resource::ShaderStages::COMPUTE);
+    let indices: Vec<_> = bind_group.iter().map(|entry| entry.binding).collect();
+    assert_eq!(indices, [3, 4, 0]);
+    assert!(HelloData::shader().is_none());
+}
+
+#[test]
+fn test_data_macro_groups() {
+    #[derive(Clone, ShaderDataDetails)]
+    #[entry("main")]
+    pub struct HelloData {
+        pub _a: Vec<u32>,
+        #[group(2)]
+        pub _b: Vec<u32>,
+        #[group(2)]
+        #[uniform]
+        pub _c: u32,
+        pub _d: Vec<u32>,
+    }
+
+    let bind_group = HelloData::buffer_entries(render_resource::ShaderStages::COMPUTE);
+    let indices: Vec<_> = bind_group.iter().map(|entry| entry.binding).collect();
+    assert_eq!(indices, [0, 0, 1, 1]);
+    assert_eq!(HelloData::groups(), [0, 2, 2, 0]);
+}
+
+#[test]
+fn test_data_macro_readback() {
+    #[derive(Clone, ShaderDataDetails)]
+    #[entry("main")]
+    pub struct HelloData {
+        pub _a: Vec<u32>,
+        #[texture(ReadWrite, R32Float, D2)]
+        pub _b: ImageBuilder<R32Float, D2>,
+        #[texture(WriteOnly, Rgba8Unorm, D2)]
+        pub _c: ImageBuilder<bevy_shader_helper::texture_details::Rgba8Unorm, D2>,
+    }
+
+    let bytes: Vec<u8> = [1u32, 2, 3].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let a: Vec<u32> = <HelloData as ReadbackData<0, 0>>::read(&bytes);
+    assert_eq!(a, [1, 2, 3]);
+    let b: Vec<f32> = <HelloData as ReadbackData<0, 1>>::read(&bytes);
+    assert_eq!(b.len(), 3);
+    let c: Vec<[u8; 4]> = <HelloData as ReadbackData<0, 2>>::read(&bytes);
+    assert_eq!(c, [[1, 0, 0, 0], [2, 0, 0, 0], [3, 0, 0, 0]]);
+}
+
+#[test]
+fn test_data_macro_workgroup_sizing() {
+    #[derive(Clone, ShaderDataDetails)]
+    #[entry("main")]
+    #[entry("update", "label")]
+    pub struct HelloData {
+        pub _a: Vec<u32>,
+        #[read_only]
+        pub _b: [bevy_shader_helper::bevy::math::Vec3; 4],
+        #[texture(ReadWrite, R32Float, D2)]
+        pub _c: ImageBuilder<R32Float, D2>,
+        #[uniform]
+        pub _d: bevy_shader_helper::bevy::math::Vec2,
+    }
+
+    assert_eq!(HelloData::entry_names(), ["main", "update"]);
+    assert_eq!(HelloData::entry_labels(), [None, Some("label")]);
+    assert_eq!(HelloData::element_sizes(), [4, 16, 0, 8]);
+}
+
+#[test]
+fn test_data_macro_texture_formats() {
+    use bevy_shader_helper::texture_details::{D1, D2Array, D3, Rgba8Unorm, Rgba16Float};
+
+    #[derive(Clone, ShaderDataDetails)]
+    #[entry("main")]
+    pub struct TextureData {
+        #[texture(WriteOnly, Rgba8Unorm, D1)]
+        pub _a: ImageBuilder<Rgba8Unorm, D1>,
+        #[texture(ReadOnly, Rgba16Float, D2Array)]
+        pub _b: ImageBuilder<Rgba16Float, D2Array>,
+        #[texture(ReadWrite, R32Float, D3)]
+        pub _c: ImageBuilder<R32Float, D3>,
+    }
+
+    let bind_group = TextureData::buffer_entries(render_resource::ShaderStages::COMPUTE);
+    let views: Vec<_> = bind_group
+        .iter()
+        .map(|entry| match entry.ty {
+            render_resource::BindingType::StorageTexture { view_dimension, .. } => view_dimension,
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(
+        views,
+        [
+            render_resource::TextureViewDimension::D1,
+            render_resource::TextureViewDimension::D2Array,
+            render_resource::TextureViewDimension::D3,
+        ]
+    );
 }
@@ -10,4 +10,6 @@ fn test_entry_macro() {
 
     assert_eq!(TestEntry::Main.as_key(), 0);    
     assert_eq!(TestEntry::Update.as_key(), 1);    
+    assert_eq!(TestEntry::Main.name(), "Main");
+    assert_eq!(TestEntry::Update.name(), "Update");
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+@group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+@compute @workgroup_size(64) fn init(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if global_id.x < arrayLength(&values) {
+        values[global_id.x] = global_id.x;
+    }
+}
+
+@compute @workgroup_size(64) fn add(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if global_id.x < arrayLength(&values) {
+        values[global_id.x] += 1u;
+    }
+}
@@ -0,0 +1,25 @@
+use bevy_shader_helper::prelude::*;
+use shader::{SumData, SumEntries, SumShaderPlugin};
+
+mod shader {
+    include!(concat!(env!("OUT_DIR"), "/sum.rs"));
+}
+
+fn main() {
+    let binding = Workgroups::Binding {
+        group: 0,
+        binding: 0,
+    };
+    let shader = SumShaderPlugin::builder()
+        .initial_data(SumData {
+            values: vec![0; 256],
+        })
+        .on_startup([Entry::new(SumEntries::Init, binding)])
+        .on_update([Entry::new(SumEntries::Add, binding)])
+        .build();
+
+    match ComputeRunner::new(shader).iterations(10).run() {
+        Ok(results) => println!("{:?}", results.get::<0, 0>()),
+        Err(error) => eprintln!("{error}"),
+    }
+}
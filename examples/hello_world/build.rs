@@ -0,0 +1,3 @@
+fn main() {
+    bevy_shader_build::compile_shader_dir("assets").expect("Failed to generate shader bindings");
+}
@@ -1,3 +1,42 @@
+use bevy::{math::vec3, prelude::*, render::render_resource::Extent3d};
+use bevy_shader_helper::prelude::*;
+use shader::{Foo, HelloBuffers, HelloData, HelloEntries, HelloShaderPlugin};
+
+mod shader {
+    include!(concat!(env!("OUT_DIR"), "/hello.rs"));
+}
+
 fn main() {
-    println!("Hello, world!");
+    let shader = HelloShaderPlugin::builder()
+        .initial_data(HelloData {
+            a: vec![1, 2, 3],
+            b: Foo { bar: 1, bazz: 2. },
+            c: vec3(1., 2., 3.),
+            d: Extent3d {
+                width: 3,
+                height: 1,
+                depth_or_array_layers: 1,
+            }
+            .into(),
+        })
+        .on_startup([(HelloEntries::Main, (3, 1, 1)).into()])
+        // One invocation for every element of `a`
+        .on_update([Entry::new(
+            HelloEntries::Update,
+            Workgroups::Binding {
+                group: 0,
+                binding: 0,
+            },
+        )])
+        .build();
+
+    App::new()
+        .add_plugins((DefaultPlugins, shader))
+        .add_systems(Startup, setup_readers)
+        .run();
+}
+
+fn setup_readers(mut commands: Commands, buffers: Res<HelloBuffers>) {
+    commands.spawn(buffers.readback_a(|data| info!(?data)));
+    commands.spawn(buffers.readback_d(|data| info!(?data)));
 }
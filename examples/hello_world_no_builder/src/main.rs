@@ -1,8 +1,4 @@
-use bevy::{
-    math::vec3,
-    prelude::*,
-    render::{gpu_readback::ReadbackComplete, render_resource::Extent3d},
-};
+use bevy::{math::vec3, prelude::*, render::render_resource::Extent3d};
 use bevy_shader_helper::prelude::*;
 use shader::{Foo, HelloBuffers, HelloData, HelloEntries, HelloShaderPlugin};
 
@@ -32,16 +28,6 @@ fn main() {
 }
 
 fn setup_readers(mut commands: Commands, buffers: Res<HelloBuffers>) {
-    commands
-        .spawn(buffers.a.readback())
-        .observe(|t: Trigger<ReadbackComplete>| {
-            let data: Vec<u32> = t.event().to_shader_type();
-            info!(?data);
-        });
-    commands
-        .spawn(buffers.d.readback())
-        .observe(|t: Trigger<ReadbackComplete>| {
-            let data: Vec<f32> = t.event().to_shader_type();
-            info!(?data);
-        });
+    commands.spawn(buffers.readback_a(|data| info!(?data)));
+    commands.spawn(buffers.readback_d(|data| info!(?data)));
 }
@@ -15,6 +15,7 @@ pub struct Foo {
 }
 
 #[derive(ShaderDataDetails, Clone)]
+#[shader("shaders/hello.wgsl")]
 #[entry("main")]
 #[entry("update")]
 pub struct HelloData {